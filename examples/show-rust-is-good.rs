@@ -5,6 +5,7 @@ use cortex_m::peripheral::Peripherals;
 use cortex_m_rt::entry;
 use panic_semihosting as _;
 
+use on_off_sequence_output::morse;
 use on_off_sequence_output::prelude::*;
 use stm32f4xx_hal::{delay::Delay, prelude::*, stm32};
 
@@ -33,7 +34,9 @@ fn main() -> ! {
 
     // Init done -> Now we can put everything in operation
 
-    ledout.set_morse("RUST IS GOOD  ", Repeat::Forever).unwrap();
+    // Encoded at compile time - no encoder table and no unwrap() on target
+    let (states, len) = morse!("RUST IS GOOD  ");
+    ledout.set(states, len, Repeat::Forever);
     loop {
         delay.delay_ms(100_u16);
         ledout.update().unwrap();
@@ -0,0 +1,291 @@
+//! Batched output of many LEDs behind shift registers or GPIO expanders
+//!
+//! Writing each LED on its own costs a shift-out or bus transaction per
+//! LED. A [`BatchOutput`] runs the sequences of up to 32 LEDs, computes all
+//! their levels in one `update()` pass and flushes them at once by a
+//! [`BatchWrite`], only if a level changed. Each LED runs its own on/off
+//! sequence with the scaling and repetitions of `OnOffSequenceOutput`; the
+//! update scale applies to every LED.
+//!
+//! * [`ShiftRegister`] - a chain of 74HC595 shift registers, bit-banged on
+//!   data, clock and latch pins
+//! * [`I2cExpander`] - an I2C GPIO expander taking its outputs as plain
+//!   bytes, e.g. PCF8574 or PCF8575, optionally prefixed by a register
+//!   address, e.g. the `OLAT` register of a MCP23017
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use on_off_sequence_output::batch::{BatchOutput, ShiftRegister};
+//! use on_off_sequence_output::prelude::*;
+//!
+//! // two chained 74HC595
+//! let chain = ShiftRegister::new(data_pin, clock_pin, latch_pin, 16);
+//! let mut leds: BatchOutput<_, 16> = BatchOutput::new(chain, UPDATE_SCALE).unwrap();
+//! leds.set(0, 0b0101, 4, Repeat::Forever);
+//! leds.set(9, 0b0011, 4, Repeat::Times(3));
+//! loop {
+//!    leds.update().unwrap();
+//!    wait(1.ms());
+//! }
+//! ```
+
+use crate::progress::LedSequence;
+use crate::{OutputUpdate, Repeat};
+use embedded_hal::blocking::i2c;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Maximum number of LEDs of a batch
+pub const MAX_NUMBER_OF_LEDS: usize = 32;
+
+/// Batch output errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BatchError {
+    /// The writer has less outputs than there are LEDs
+    TooManyLeds,
+}
+
+/// Writes the levels of all LEDs at once
+pub trait BatchWrite {
+    /// Error type of the write
+    type Error;
+
+    /// Number of outputs written, LED `n` needs more than `n`
+    fn bits(&self) -> u8;
+
+    /// Writes the levels, bit `n` is the level of LED `n`, 1 equals high
+    fn write(&mut self, levels: u32) -> Result<(), Self::Error>;
+}
+
+/// Chain of 74HC595 shift registers
+///
+/// LED 0 is the output `QA` of the register connected to the data pin.
+pub struct ShiftRegister<P: OutputPin> {
+    /// Serial data input `SER`
+    pub data: P,
+    /// Shift register clock `SRCLK`, shifts on the rising edge
+    pub clock: P,
+    /// Storage register clock `RCLK`, latches on the rising edge
+    pub latch: P,
+    /// Number of outputs of the chain
+    bits: u8,
+}
+
+impl<P: OutputPin> ShiftRegister<P> {
+    /// Combines the pins of a shift register chain
+    ///
+    /// # Arguments
+    ///
+    /// * `data`, `clock`, `latch` - As output initialized GPIO pins, clock
+    ///   and latch low
+    /// * `bits` - Number of outputs of the chain, e.g. 8 per register
+    ///
+    /// # Panics
+    ///
+    /// If `bits` is zero or more than 32
+    pub fn new(data: P, clock: P, latch: P, bits: u8) -> Self {
+        if bits == 0 || bits as usize > MAX_NUMBER_OF_LEDS {
+            panic!("Must be 1 up to 32 bits");
+        };
+        Self {
+            data,
+            clock,
+            latch,
+            bits,
+        }
+    }
+}
+
+impl<P: OutputPin> BatchWrite for ShiftRegister<P> {
+    type Error = P::Error;
+
+    fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    fn write(&mut self, levels: u32) -> Result<(), Self::Error> {
+        // the bit shifted first ends up at the far end of the chain
+        for bit in (0..self.bits).rev() {
+            if levels & (1 << bit) != 0 {
+                self.data.set_high()?;
+            } else {
+                self.data.set_low()?;
+            }
+            self.clock.set_high()?;
+            self.clock.set_low()?;
+        }
+        self.latch.set_high()?;
+        self.latch.set_low()
+    }
+}
+
+/// I2C GPIO expander with byte wise outputs
+///
+/// The levels are written little endian, i.e. LED 0 is output 0 of the
+/// first byte.
+pub struct I2cExpander<I: i2c::Write> {
+    /// The I2C bus
+    pub i2c: I,
+    /// 7-bit address of the expander
+    pub address: u8,
+    /// Output register written first, if the expander needs one
+    register: Option<u8>,
+    /// Number of output bytes
+    bytes: usize,
+}
+
+impl<I: i2c::Write> I2cExpander<I> {
+    /// Combines the bus and the address of an expander
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c` - The I2C bus
+    /// * `address` - 7-bit address of the expander
+    /// * `bytes` - Number of output bytes, e.g. 1 for a PCF8574
+    ///
+    /// # Panics
+    ///
+    /// If `bytes` is zero or more than 4
+    pub fn new(i2c: I, address: u8, bytes: usize) -> Self {
+        if bytes == 0 || bytes > 4 {
+            panic!("Must be 1 up to 4 bytes");
+        };
+        Self {
+            i2c,
+            address,
+            register: None,
+            bytes,
+        }
+    }
+
+    /// The expander with an output register written first
+    pub fn with_register(mut self, register: u8) -> Self {
+        self.register = Some(register);
+        self
+    }
+}
+
+impl<I: i2c::Write> BatchWrite for I2cExpander<I> {
+    type Error = I::Error;
+
+    fn bits(&self) -> u8 {
+        8 * self.bytes as u8
+    }
+
+    fn write(&mut self, levels: u32) -> Result<(), Self::Error> {
+        let mut buffer = [0u8; 5];
+        let mut len = 0;
+        if let Some(register) = self.register {
+            buffer[0] = register;
+            len = 1;
+        }
+        buffer[len..len + self.bytes].copy_from_slice(&levels.to_le_bytes()[..self.bytes]);
+        len += self.bytes;
+        self.i2c.write(self.address, &buffer[..len])
+    }
+}
+
+/// Output of the sequences of many LEDs, written at once
+pub struct BatchOutput<W: BatchWrite, const N: usize> {
+    /// Writes the levels of all LEDs
+    pub writer: W,
+
+    /// The sequences of the LEDs
+    leds: [LedSequence; N],
+
+    /// The levels written last, None before the first write
+    written: Option<u32>,
+}
+
+impl<W: BatchWrite, const N: usize> BatchOutput<W, N> {
+    /// Initializes a new batch output
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Writes the levels of all LEDs
+    /// * `update_scale` - Scale factor of every LED:
+    ///   led state change frequency = update frequency * update_scale
+    ///
+    /// # Notes
+    ///
+    /// * Default is all LEDs off
+    ///
+    /// # Returns
+    ///
+    /// The batch output, or Err(BatchError::TooManyLeds) if the writer has
+    /// less outputs than `N`
+    ///
+    /// # Panics
+    ///
+    /// If there are no LEDs or more than 32
+    pub fn new(writer: W, update_scale: u16) -> Result<Self, BatchError> {
+        if N == 0 || N > MAX_NUMBER_OF_LEDS {
+            panic!("Must be 1 up to 32 LEDs");
+        };
+        if N > usize::from(writer.bits()) {
+            return Err(BatchError::TooManyLeds);
+        }
+        Ok(Self {
+            writer,
+            leds: [LedSequence::off(update_scale); N],
+            written: None,
+        })
+    }
+
+    /// Set a new output of a single LED, see `OnOffSequenceOutput::set()`
+    ///
+    /// # Panics
+    ///
+    /// If the LED is out of range, or on the number of output states like
+    /// `set()` does
+    pub fn set(
+        &mut self,
+        led: usize,
+        output_states: u128,
+        number_of_output_states: u16,
+        repeat: Repeat,
+    ) {
+        self.leds[led].set(output_states, number_of_output_states, repeat);
+    }
+
+    /// The levels of all LEDs, bit `n` is LED `n`, 1 equals high
+    pub fn levels(&self) -> u32 {
+        self.leds
+            .iter()
+            .enumerate()
+            .fold(0, |levels, (index, led)| {
+                levels | (u32::from(led.lit) << index)
+            })
+    }
+}
+
+impl<W: BatchWrite, const N: usize> OutputUpdate for BatchOutput<W, N> {
+    type Error = W::Error;
+
+    /// Progresses the sequences of all LEDs and writes their levels if any
+    /// changed
+    ///
+    /// # Returns
+    ///
+    /// true if the sequences of all LEDs are completed
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        let mut completed = true;
+        for led in self.leds.iter_mut() {
+            led.tick();
+            completed &= led.progress.is_completed();
+        }
+
+        let levels = self.levels();
+        if self.written != Some(levels) {
+            // written again by the next update if it fails
+            self.written = None;
+            self.writer.write(levels)?;
+            self.written = Some(levels);
+        }
+        Ok(completed)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,183 @@
+/// Tests for the batched output
+use super::*;
+use crate::tests::mocks::*;
+
+/// Records the written levels
+struct RecordingWriter {
+    writes: [u32; 8],
+    number_of_writes: usize,
+}
+
+impl RecordingWriter {
+    fn new() -> Self {
+        Self {
+            writes: [0; 8],
+            number_of_writes: 0,
+        }
+    }
+
+    fn writes(&self) -> &[u32] {
+        &self.writes[..self.number_of_writes]
+    }
+}
+
+impl BatchWrite for RecordingWriter {
+    type Error = MockedOutputPinError;
+
+    fn bits(&self) -> u8 {
+        32
+    }
+
+    fn write(&mut self, levels: u32) -> Result<(), Self::Error> {
+        self.writes[self.number_of_writes] = levels;
+        self.number_of_writes += 1;
+        Ok(())
+    }
+}
+
+/// Records the last I2C write
+struct MockedI2c {
+    address: u8,
+    bytes: [u8; 8],
+    len: usize,
+}
+
+impl i2c::Write for MockedI2c {
+    type Error = MockedOutputPinError;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.address = address;
+        self.bytes[..bytes.len()].copy_from_slice(bytes);
+        self.len = bytes.len();
+        Ok(())
+    }
+}
+
+mod batch_output {
+    use super::*;
+
+    #[test]
+    fn writes_changed_levels_only() -> Result<(), MockedOutputPinError> {
+        let mut leds: BatchOutput<_, 3> = BatchOutput::new(RecordingWriter::new(), 1).unwrap();
+        leds.set(0, 0b01, 2, Repeat::Never);
+        leds.set(2, 0b11, 2, Repeat::Never);
+        assert!(!leds.update()?);
+        assert_eq!(0b101, leds.levels());
+        assert!(leds.update()?);
+        assert!(leds.update()?);
+        assert_eq!(&[0b101, 0b100], leds.writer.writes());
+        Ok(())
+    }
+
+    #[test]
+    fn update_scale() -> Result<(), MockedOutputPinError> {
+        let mut leds: BatchOutput<_, 2> = BatchOutput::new(RecordingWriter::new(), 2).unwrap();
+        leds.set(1, 0b01, 2, Repeat::Never);
+        assert!(!leds.update()?);
+        assert!(!leds.update()?);
+        assert!(!leds.update()?);
+        assert!(leds.update()?);
+        assert_eq!(&[0b00, 0b10, 0b00], leds.writer.writes());
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_leds() {
+        let _: BatchOutput<_, 33> = BatchOutput::new(RecordingWriter::new(), 1).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn led_out_of_range() {
+        let mut leds: BatchOutput<_, 2> = BatchOutput::new(RecordingWriter::new(), 1).unwrap();
+        leds.set(2, 0b1, 1, Repeat::Never);
+    }
+
+    #[test]
+    fn more_leds_than_shift_register_bits() {
+        let data = MockedOutputPin::expected(0, 0b0_u128);
+        let clock = MockedOutputPin::expected(0, 0b0_u128);
+        let latch = MockedOutputPin::expected(0, 0b0_u128);
+        let chain = ShiftRegister::new(data, clock, latch, 8);
+        let leds: Result<BatchOutput<_, 9>, _> = BatchOutput::new(chain, 1);
+        assert_eq!(Some(BatchError::TooManyLeds), leds.err());
+    }
+
+    #[test]
+    fn more_leds_than_expander_outputs() {
+        let i2c = MockedI2c {
+            address: 0,
+            bytes: [0; 8],
+            len: 0,
+        };
+        let expander = I2cExpander::new(i2c, 0x20, 1);
+        let leds: Result<BatchOutput<_, 9>, _> = BatchOutput::new(expander, 1);
+        assert_eq!(Some(BatchError::TooManyLeds), leds.err());
+    }
+
+    #[test]
+    fn fewer_leds_than_expander_outputs() {
+        let i2c = MockedI2c {
+            address: 0,
+            bytes: [0; 8],
+            len: 0,
+        };
+        let expander = I2cExpander::new(i2c, 0x20, 2);
+        let leds: Result<BatchOutput<_, 9>, _> = BatchOutput::new(expander, 1);
+        assert!(leds.is_ok());
+    }
+}
+
+mod shift_register {
+    use super::*;
+
+    #[test]
+    fn shifts_last_led_first_and_latches() -> Result<(), MockedOutputPinError> {
+        let data = MockedOutputPin::expected(8, 0b1100_0000_u128);
+        let clock = MockedOutputPin::expected(16, 0x5555_u128);
+        let latch = MockedOutputPin::expected(2, 0b01_u128);
+        let mut chain = ShiftRegister::new(data, clock, latch, 8);
+        chain.write(0b0000_0011)
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_bits() {
+        let data = MockedOutputPin::expected(0, 0b0_u128);
+        let clock = MockedOutputPin::expected(0, 0b0_u128);
+        let latch = MockedOutputPin::expected(0, 0b0_u128);
+        let _ = ShiftRegister::new(data, clock, latch, 33);
+    }
+}
+
+mod i2c_expander {
+    use super::*;
+
+    #[test]
+    fn writes_bytes_little_endian() -> Result<(), MockedOutputPinError> {
+        let i2c = MockedI2c {
+            address: 0,
+            bytes: [0; 8],
+            len: 0,
+        };
+        let mut expander = I2cExpander::new(i2c, 0x20, 2);
+        expander.write(0x0201)?;
+        assert_eq!(0x20, expander.i2c.address);
+        assert_eq!(&[0x01, 0x02], &expander.i2c.bytes[..expander.i2c.len]);
+        Ok(())
+    }
+
+    #[test]
+    fn writes_register_first() -> Result<(), MockedOutputPinError> {
+        let i2c = MockedI2c {
+            address: 0,
+            bytes: [0; 8],
+            len: 0,
+        };
+        let mut expander = I2cExpander::new(i2c, 0x20, 1).with_register(0x14);
+        expander.write(0x0281)?;
+        assert_eq!(&[0x14, 0x81], &expander.i2c.bytes[..expander.i2c.len]);
+        Ok(())
+    }
+}
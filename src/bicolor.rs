@@ -0,0 +1,227 @@
+//! Output of color sequences on bi-color LEDs
+//!
+//! A two-lead bi-color LED contains a red and a green LED in anti-parallel,
+//! so it is driven by two pins in opposite directions: red lights if the red
+//! pin is high and the green pin is low, green vice versa. Both pins low
+//! switch the LED off. Yellow is shown by alternating red and green at every
+//! update.
+//!
+//! [`BiColor`] maps the colors to the pins as a [`SymbolOutput`] of two bits
+//! per color, [`BiColorOutput`] plays color sequences on it with a
+//! [`SymbolSequenceOutput`], i.e. with the same update scaling and
+//! repetitions as `OnOffSequenceOutput`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use on_off_sequence_output::bicolor::{BiColorOutput, Color};
+//! use on_off_sequence_output::prelude::*;
+//!
+//! let mut led = BiColorOutput::new(red_pin, green_pin, UPDATE_SCALE);
+//! led.set(&[Color::Green, Color::Off, Color::Yellow, Color::Off], Repeat::Forever);
+//! loop {
+//!    led.update().unwrap();
+//!    wait(1.ms());
+//! }
+//! ```
+
+use crate::sequencer::{max_number_of_symbols, SymbolOutput, SymbolSequenceOutput};
+use crate::{OutputUpdate, Repeat};
+use embedded_hal::digital::v2::OutputPin;
+
+/// Maximum number of colors in a sequence, two bits each in a u128
+pub const MAX_NUMBER_OF_COLORS: u16 = max_number_of_symbols(BITS_PER_COLOR);
+
+/// Bits per color symbol
+const BITS_PER_COLOR: u8 = 2;
+
+/// Color of a bi-color LED
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Color {
+    /// Both pins low
+    Off,
+    /// Red pin high, green pin low
+    Red,
+    /// Red pin low, green pin high
+    Green,
+    /// Red and green alternating at every update
+    Yellow,
+}
+
+impl Color {
+    const fn to_bits(self) -> u8 {
+        match self {
+            Color::Off => 0b00,
+            Color::Red => 0b01,
+            Color::Green => 0b10,
+            Color::Yellow => 0b11,
+        }
+    }
+
+    const fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => Color::Off,
+            0b01 => Color::Red,
+            0b10 => Color::Green,
+            _ => Color::Yellow,
+        }
+    }
+}
+
+/// Bi-color LED on two pins, the symbols are the bits of a [`Color`]
+pub struct BiColor<R, G>
+where
+    R: OutputPin,
+    G: OutputPin<Error = R::Error>,
+{
+    /// The pin which lights red if high
+    pub red: R,
+
+    /// The pin which lights green if high
+    pub green: G,
+
+    /// Whether red is lit while yellow is shown
+    yellow_red: bool,
+}
+
+impl<R, G> BiColor<R, G>
+where
+    R: OutputPin,
+    G: OutputPin<Error = R::Error>,
+{
+    /// Combines the pins of a bi-color LED
+    pub fn new(red: R, green: G) -> Self {
+        Self {
+            red,
+            green,
+            yellow_red: false,
+        }
+    }
+
+    fn drive(&mut self, color: Color) -> Result<(), R::Error> {
+        let (red, green) = match color {
+            Color::Off => (false, false),
+            Color::Red => (true, false),
+            Color::Green => (false, true),
+            Color::Yellow => {
+                self.yellow_red = !self.yellow_red;
+                (self.yellow_red, !self.yellow_red)
+            }
+        };
+        // switch the lit pin off first, so the pins never drive the LED
+        // against each other
+        if red {
+            self.green.set_low()?;
+            self.red.set_high()?;
+        } else if green {
+            self.red.set_low()?;
+            self.green.set_high()?;
+        } else {
+            self.red.set_low()?;
+            self.green.set_low()?;
+        }
+        Ok(())
+    }
+}
+
+impl<R, G> SymbolOutput for BiColor<R, G>
+where
+    R: OutputPin,
+    G: OutputPin<Error = R::Error>,
+{
+    type Error = R::Error;
+
+    fn output(&mut self, symbol: u8) -> Result<(), Self::Error> {
+        self.yellow_red = false;
+        self.drive(Color::from_bits(symbol))
+    }
+
+    /// Alternates the pins while yellow is shown
+    fn refresh(&mut self, symbol: u8) -> Result<(), Self::Error> {
+        match Color::from_bits(symbol) {
+            Color::Yellow => self.drive(Color::Yellow),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Output of color sequences on a bi-color LED
+pub struct BiColorOutput<R, G>
+where
+    R: OutputPin,
+    G: OutputPin<Error = R::Error>,
+{
+    /// The sequence of color symbols, the LED is its output
+    pub sequence: SymbolSequenceOutput<BiColor<R, G>>,
+}
+
+impl<R, G> BiColorOutput<R, G>
+where
+    R: OutputPin,
+    G: OutputPin<Error = R::Error>,
+{
+    /// Initializes a new bi-color led output
+    ///
+    /// # Arguments
+    ///
+    /// * `red` - An as output initialized GPIO pin, lights red if high
+    /// * `green` - An as output initialized GPIO pin, lights green if high
+    /// * `update_scale` - Scale factor:
+    ///   color change frequency = update frequency * update_scale
+    ///
+    /// # Notes
+    ///
+    /// * Default is blinking green forever
+    pub fn new(red: R, green: G, update_scale: u16) -> Self {
+        let mut sequence =
+            SymbolSequenceOutput::new(BiColor::new(red, green), BITS_PER_COLOR, update_scale);
+        sequence.set_packed(u128::from(Color::Green.to_bits()), 2, Repeat::Forever);
+        Self { sequence }
+    }
+
+    /// Set a new color sequence
+    ///
+    /// # Arguments
+    ///
+    /// * `colors` - Colors in the order they are shown
+    /// * `repeat` - How often is the sequence repeated
+    ///
+    /// # Panics
+    ///
+    /// If there are no colors or more than 64 colors
+    pub fn set(&mut self, colors: &[Color], repeat: Repeat) {
+        if colors.len() > MAX_NUMBER_OF_COLORS as usize {
+            panic!("Must be at most 64 colors");
+        };
+        if colors.is_empty() {
+            panic!("Zero colors do not make sense");
+        };
+        let mut symbols = 0u128;
+        for (index, color) in colors.iter().enumerate() {
+            symbols |= u128::from(color.to_bits()) << (usize::from(BITS_PER_COLOR) * index);
+        }
+        self.sequence
+            .set_packed(symbols, colors.len() as u16, repeat);
+    }
+}
+
+impl<R, G> OutputUpdate for BiColorOutput<R, G>
+where
+    R: OutputPin,
+    G: OutputPin<Error = R::Error>,
+{
+    type Error = R::Error;
+
+    /// Updates the output logic, potentially switches the color and
+    /// alternates the pins while yellow is shown
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        self.sequence.update()
+    }
+
+    fn update_by(&mut self, ticks: u16) -> Result<bool, Self::Error> {
+        self.sequence.update_by(ticks)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,101 @@
+/// Tests for the bi-color LED output
+use super::*;
+use crate::tests::mocks::*;
+
+#[test]
+fn colors() -> Result<(), MockedOutputPinError> {
+    let red = MockedOutputPin::expected(3, 0b001_u128);
+    let green = MockedOutputPin::expected(3, 0b010_u128);
+    let mut ledout = BiColorOutput::new(red, green, 1);
+    ledout.set(&[Color::Red, Color::Green, Color::Off], Repeat::Never);
+    assert!(!ledout.update()?);
+    assert!(!ledout.update()?);
+    assert!(ledout.update()?);
+    assert!(ledout.update()?);
+    Ok(())
+}
+
+#[test]
+fn update_scaling() -> Result<(), MockedOutputPinError> {
+    let red = MockedOutputPin::expected(2, 0b01_u128);
+    let green = MockedOutputPin::expected(2, 0b00_u128);
+    let mut ledout = BiColorOutput::new(red, green, 3);
+    ledout.set(&[Color::Red, Color::Off], Repeat::Never);
+    for _ in 1..6 {
+        assert!(!ledout.update()?);
+    }
+    assert!(ledout.update()?);
+    Ok(())
+}
+
+#[test]
+fn yellow_alternates_at_every_update() -> Result<(), MockedOutputPinError> {
+    let red = MockedOutputPin::expected(5, 0b0_0101_u128);
+    let green = MockedOutputPin::expected(5, 0b0_1010_u128);
+    let mut ledout = BiColorOutput::new(red, green, 2);
+    ledout.set(&[Color::Yellow, Color::Yellow, Color::Off], Repeat::Never);
+    // nothing shown yet
+    ledout.update()?;
+    // four updates yellow, then off
+    for _ in 0..5 {
+        ledout.update()?;
+    }
+    Ok(())
+}
+
+#[test]
+fn yellow_alternates_when_completed() -> Result<(), MockedOutputPinError> {
+    let red = MockedOutputPin::expected(4, 0b0101_u128);
+    let green = MockedOutputPin::expected(4, 0b1010_u128);
+    let mut ledout = BiColorOutput::new(red, green, 1);
+    ledout.set(&[Color::Yellow], Repeat::Never);
+    for _ in 0..4 {
+        assert!(ledout.update()?);
+    }
+    Ok(())
+}
+
+#[test]
+fn repetitions() -> Result<(), MockedOutputPinError> {
+    let red = MockedOutputPin::expected(4, 0b0000_u128);
+    let green = MockedOutputPin::expected(4, 0b0101_u128);
+    let mut ledout = BiColorOutput::new(red, green, 1);
+    ledout.set(&[Color::Green, Color::Off], Repeat::Times(1));
+    for _ in 1..4 {
+        assert!(!ledout.update()?);
+    }
+    assert!(ledout.update()?);
+    Ok(())
+}
+
+#[test]
+fn longest_sequence() -> Result<(), MockedOutputPinError> {
+    let red = MockedOutputPin::expected(64, 0b0_u128);
+    let green = MockedOutputPin::expected(64, 1_u128 << 63);
+    let mut ledout = BiColorOutput::new(red, green, 1);
+    let mut colors = [Color::Off; 64];
+    colors[63] = Color::Green;
+    ledout.set(&colors, Repeat::Never);
+    for _ in 0..64 {
+        ledout.update()?;
+    }
+    Ok(())
+}
+
+#[test]
+#[should_panic]
+fn too_many_colors() {
+    let red = MockedOutputPin::expected(0, 0b0_u128);
+    let green = MockedOutputPin::expected(0, 0b0_u128);
+    let mut ledout = BiColorOutput::new(red, green, 1);
+    ledout.set(&[Color::Off; 65], Repeat::Never);
+}
+
+#[test]
+#[should_panic]
+fn no_colors() {
+    let red = MockedOutputPin::expected(0, 0b0_u128);
+    let green = MockedOutputPin::expected(0, 0b0_u128);
+    let mut ledout = BiColorOutput::new(red, green, 1);
+    ledout.set(&[], Repeat::Never);
+}
@@ -0,0 +1,395 @@
+//! Preview, export and validate on/off patterns without writing firmware
+//!
+//! The pattern is given as bit pattern, morse text or in the notation of the
+//! `dsl` module. It is played by `OnOffSequenceOutput` on a simulated pin,
+//! one update per tick, so the timeline is exactly what a device shows.
+//!
+//! ```text
+//! onoff-cli --dsl "#_## x2" --update-scale 250 --tick-ms 1
+//! onoff-cli --morse SOS --repeat forever --export vcd --output sos.vcd
+//! ```
+
+use embedded_hal::digital::v2::OutputPin;
+use on_off_sequence_output::pattern::{Pattern, MAX_ENCODED_SIZE};
+use on_off_sequence_output::prelude::*;
+use on_off_sequence_output::{command, dsl, morse};
+use std::convert::Infallible;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::{env, fs, io, process};
+
+const USAGE: &str = "\
+Usage: onoff-cli (--bits <0b...> | --morse <text> | --dsl <text>) [options]
+
+Pattern (exactly one):
+  --bits <0b...>        bit pattern, lsb is played first, every digit is a state
+  --morse <text>        morse code
+  --dsl <text>          on/off notation, e.g. \"#_## x2\" or \"on 200ms, off 1000ms\"
+
+Options:
+  --update-scale <n>    updates per state (default 1)
+  --tick-ms <ms>        period of the updates in milliseconds (default 1)
+  --state-ms <ms>       duration of one state for durations in the notation
+                        (default update scale times tick period)
+  --repeat <r>          never, forever or a number of repetitions (default never)
+  --ticks <n>           number of updates to simulate (default until completed,
+                        one run for patterns repeated forever)
+  --export <format>     vcd, wav or json instead of the preview
+  --output <file>       write the export to a file instead of stdout
+";
+
+/// Sample rate of the WAV export
+const WAV_SAMPLE_RATE: u32 = 8000;
+
+/// Upper bound of simulated updates
+const MAX_TICKS: u64 = 10_000_000;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Source {
+    Bits(String),
+    Morse(String),
+    Dsl(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Export {
+    Vcd,
+    Wav,
+    Json,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Options {
+    source: Source,
+    update_scale: u16,
+    tick_ms: f64,
+    state_ms: Option<u32>,
+    repeat: Repeat,
+    ticks: Option<u64>,
+    export: Option<Export>,
+    output: Option<String>,
+}
+
+/// Parse the command line arguments, without the program name
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut source = None;
+    let mut update_scale = 1;
+    let mut tick_ms = 1.0;
+    let mut state_ms = None;
+    let mut repeat = Repeat::Never;
+    let mut ticks = None;
+    let mut export = None;
+    let mut output = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value of {}", arg))
+        };
+        match arg.as_str() {
+            "--bits" | "--morse" | "--dsl" => {
+                if source.is_some() {
+                    return Err("more than one pattern given".into());
+                }
+                let text = value()?;
+                source = Some(match arg.as_str() {
+                    "--bits" => Source::Bits(text),
+                    "--morse" => Source::Morse(text),
+                    _ => Source::Dsl(text),
+                });
+            }
+            "--update-scale" => update_scale = parse_number(&value()?, "update scale")?,
+            "--tick-ms" => {
+                tick_ms = value()?
+                    .parse()
+                    .ok()
+                    .filter(|tick_ms: &f64| *tick_ms > 0.0 && tick_ms.is_finite())
+                    .ok_or("invalid tick period")?
+            }
+            "--state-ms" => state_ms = Some(parse_number(&value()?, "state duration")?),
+            "--repeat" => {
+                repeat = match value()?.as_str() {
+                    "never" => Repeat::Never,
+                    "forever" => Repeat::Forever,
+                    times => Repeat::Times(parse_number(times, "repeat")?),
+                }
+            }
+            "--ticks" => ticks = Some(parse_number(&value()?, "number of ticks")?),
+            "--export" => {
+                export = Some(match value()?.as_str() {
+                    "vcd" => Export::Vcd,
+                    "wav" => Export::Wav,
+                    "json" => Export::Json,
+                    _ => return Err("export format must be vcd, wav or json".into()),
+                })
+            }
+            "--output" => output = Some(value()?),
+            _ => return Err(format!("unknown argument {}", arg)),
+        }
+    }
+    if update_scale == 0 {
+        return Err("update scale must be at least 1".into());
+    }
+    Ok(Options {
+        source: source.ok_or("no pattern given")?,
+        update_scale,
+        tick_ms,
+        state_ms,
+        repeat,
+        ticks,
+        export,
+        output,
+    })
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str, what: &str) -> Result<T, String> {
+    text.parse()
+        .map_err(|_| format!("invalid {}: {}", what, text))
+}
+
+/// The output states of the pattern, validated by the crate's encoders
+fn output_states(options: &Options) -> Result<(u128, u16), String> {
+    match &options.source {
+        Source::Bits(text) => {
+            if !text.starts_with("0b") {
+                return Err("bit pattern must start with 0b".into());
+            }
+            command::parse_states(text)
+                .map_err(|_| "bit pattern must have 1 up to 127 binary digits".into())
+        }
+        Source::Morse(text) => match morse::str_to_morse(text) {
+            Ok((_, 0)) => Err("morse text is empty".into()),
+            Ok(states) => Ok(states),
+            Err(error) => Err(morse::describe(error).into()),
+        },
+        Source::Dsl(text) => {
+            let state_ms = options.state_ms.unwrap_or_else(|| {
+                (options.tick_ms * f64::from(options.update_scale)).round() as u32
+            });
+            dsl::parse_timed(text, state_ms).map_err(|error| dsl::describe(error).into())
+        }
+    }
+}
+
+/// Pin of the simulation
+struct SimulatedPin {
+    high: bool,
+}
+
+impl OutputPin for SimulatedPin {
+    type Error = Infallible;
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.high = true;
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.high = false;
+        Ok(())
+    }
+}
+
+/// The pin level of every tick
+#[derive(Debug, PartialEq)]
+struct Timeline {
+    levels: Vec<bool>,
+    tick_ms: f64,
+}
+
+/// Plays the pattern on a simulated pin
+///
+/// The timeline starts with the first state; the last state lasts its full
+/// duration.
+fn simulate(pattern: &Pattern, options: &Options) -> Timeline {
+    let pin = SimulatedPin { high: false };
+    let mut output = OnOffSequenceOutput::new(pin, 1);
+    // output_states() rejects empty and too long patterns
+    output.set_pattern(pattern).unwrap();
+    let scale = u64::from(options.update_scale);
+    let one_run = u64::from(pattern.number_of_output_states) * scale;
+    let ticks = match (options.ticks, options.repeat) {
+        (Some(ticks), _) => Some(ticks),
+        (None, Repeat::Forever) => Some(one_run),
+        (None, _) => None,
+    };
+
+    let mut levels = Vec::new();
+    // the first state is driven after one state duration
+    for _ in 1..scale {
+        output.update().unwrap();
+    }
+    let mut tick = 0;
+    loop {
+        let completed = output.update().unwrap();
+        levels.push(output.pin.high);
+        tick += 1;
+        if ticks.map_or(completed, |ticks| tick >= ticks) || tick >= MAX_TICKS {
+            break;
+        }
+    }
+    if ticks.is_none() {
+        // the last state lasts its full duration
+        let last = *levels.last().unwrap();
+        levels.extend((1..scale).map(|_| last));
+    }
+    Timeline {
+        levels,
+        tick_ms: options.tick_ms,
+    }
+}
+
+impl Timeline {
+    fn duration_ms(&self) -> f64 {
+        self.levels.len() as f64 * self.tick_ms
+    }
+
+    /// Share of ticks the pin is high, in percent
+    fn duty_cycle(&self) -> f64 {
+        let high = self.levels.iter().filter(|&&high| high).count();
+        100.0 * high as f64 / self.levels.len().max(1) as f64
+    }
+
+    /// Pin levels with the tick they start
+    fn edges(&self) -> Vec<(usize, bool)> {
+        let mut edges: Vec<(usize, bool)> = Vec::new();
+        for (tick, &high) in self.levels.iter().enumerate() {
+            if !matches!(edges.last(), Some(&(_, last)) if last == high) {
+                edges.push((tick, high));
+            }
+        }
+        edges
+    }
+
+    /// One character per tick, `#` is high
+    fn to_text(&self) -> String {
+        self.levels
+            .iter()
+            .map(|&high| if high { '#' } else { '_' })
+            .collect()
+    }
+
+    fn to_vcd(&self) -> String {
+        let tick_us = self.tick_ms * 1000.0;
+        let mut vcd = String::new();
+        vcd.push_str("$timescale 1 us $end\n");
+        vcd.push_str("$scope module onoff $end\n");
+        vcd.push_str("$var wire 1 ! pin $end\n");
+        vcd.push_str("$upscope $end\n");
+        vcd.push_str("$enddefinitions $end\n");
+        for (tick, high) in self.edges() {
+            let time = (tick as f64 * tick_us).round() as u64;
+            let _ = writeln!(vcd, "#{}\n{}!", time, if high { 1 } else { 0 });
+        }
+        let _ = writeln!(vcd, "#{}", (self.duration_ms() * 1000.0).round() as u64);
+        vcd
+    }
+
+    /// Unsigned 8 bit mono PCM, high is full scale
+    fn to_wav(&self) -> Vec<u8> {
+        let samples_per_tick = f64::from(WAV_SAMPLE_RATE) * self.tick_ms / 1000.0;
+        let mut samples = Vec::new();
+        for (tick, &high) in self.levels.iter().enumerate() {
+            let end = ((tick + 1) as f64 * samples_per_tick).round() as usize;
+            samples.resize(end, if high { 0xff } else { 0x00 });
+        }
+        let mut wav = Vec::with_capacity(44 + samples.len());
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + samples.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+        wav.extend_from_slice(&WAV_SAMPLE_RATE.to_le_bytes());
+        wav.extend_from_slice(&WAV_SAMPLE_RATE.to_le_bytes()); // byte rate
+        wav.extend_from_slice(&1u16.to_le_bytes()); // block align
+        wav.extend_from_slice(&8u16.to_le_bytes()); // bits per sample
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&samples);
+        wav
+    }
+
+    fn to_json(&self, pattern: &Pattern) -> String {
+        let levels: Vec<&str> = self
+            .levels
+            .iter()
+            .map(|&high| if high { "1" } else { "0" })
+            .collect();
+        format!(
+            "{{\"output_states\":\"{:#b}\",\"number_of_output_states\":{},\
+             \"tick_ms\":{},\"duration_ms\":{},\"duty_cycle\":{:.1},\"levels\":[{}]}}\n",
+            pattern.output_states,
+            pattern.number_of_output_states,
+            self.tick_ms,
+            self.duration_ms(),
+            self.duty_cycle(),
+            levels.join(",")
+        )
+    }
+}
+
+/// Human readable summary of the pattern and its timeline
+fn preview(pattern: &Pattern, timeline: &Timeline) -> String {
+    let mut buffer = [0u8; MAX_ENCODED_SIZE];
+    let encoded = pattern.encode(&mut buffer).unwrap_or(&[]);
+    let encoded: Vec<String> = encoded.iter().map(|byte| format!("{:02x}", byte)).collect();
+    let mut text = String::new();
+    let _ = writeln!(text, "timeline:   {}", timeline.to_text());
+    let _ = writeln!(
+        text,
+        "duration:   {} ms ({} ticks)",
+        timeline.duration_ms(),
+        timeline.levels.len()
+    );
+    let _ = writeln!(text, "duty cycle: {:.1} %", timeline.duty_cycle());
+    let _ = writeln!(
+        text,
+        "states:     {} ({:#b})",
+        pattern.number_of_output_states, pattern.output_states
+    );
+    let _ = writeln!(text, "encoded:    {}", encoded.join(" "));
+    text
+}
+
+fn run(options: &Options) -> Result<Vec<u8>, String> {
+    let (output_states, number_of_output_states) = output_states(options)?;
+    let pattern = Pattern::new(output_states, number_of_output_states, options.repeat)
+        .with_update_scale(options.update_scale);
+    let timeline = simulate(&pattern, options);
+    Ok(match options.export {
+        None => preview(&pattern, &timeline).into_bytes(),
+        Some(Export::Vcd) => timeline.to_vcd().into_bytes(),
+        Some(Export::Wav) => timeline.to_wav(),
+        Some(Export::Json) => timeline.to_json(&pattern).into_bytes(),
+    })
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("error: {}\n\n{}", error, USAGE);
+            process::exit(2);
+        }
+    };
+    let output = match run(&options) {
+        Ok(output) => output,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            process::exit(2);
+        }
+    };
+    let written = match &options.output {
+        Some(path) => fs::write(path, output),
+        None => io::stdout().write_all(&output),
+    };
+    if let Err(error) = written {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,193 @@
+/// Tests for the pattern preview tool
+use super::*;
+
+fn options(args: &[&str]) -> Options {
+    parse_args(args.iter().map(|arg| arg.to_string())).unwrap()
+}
+
+fn timeline(args: &[&str]) -> String {
+    let options = options(args);
+    let (output_states, number_of_output_states) = output_states(&options).unwrap();
+    let pattern = Pattern::new(output_states, number_of_output_states, options.repeat)
+        .with_update_scale(options.update_scale);
+    simulate(&pattern, &options).to_text()
+}
+
+#[test]
+fn arguments() {
+    assert_eq!(
+        Options {
+            source: Source::Morse("SOS".into()),
+            update_scale: 250,
+            tick_ms: 0.5,
+            state_ms: None,
+            repeat: Repeat::Times(2),
+            ticks: None,
+            export: Some(Export::Vcd),
+            output: Some("sos.vcd".into()),
+        },
+        options(&[
+            "--morse",
+            "SOS",
+            "--update-scale",
+            "250",
+            "--tick-ms",
+            "0.5",
+            "--repeat",
+            "2",
+            "--export",
+            "vcd",
+            "--output",
+            "sos.vcd"
+        ])
+    );
+}
+
+#[test]
+fn argument_errors() {
+    let parse = |args: &[&str]| parse_args(args.iter().map(|arg| arg.to_string()));
+    assert!(parse(&[]).is_err());
+    assert!(parse(&["--bits"]).is_err());
+    assert!(parse(&["--bits", "0b1", "--dsl", "#"]).is_err());
+    assert!(parse(&["--bits", "0b1", "--update-scale", "0"]).is_err());
+    assert!(parse(&["--bits", "0b1", "--tick-ms", "-1"]).is_err());
+    assert!(parse(&["--bits", "0b1", "--export", "mp3"]).is_err());
+    assert!(parse(&["--bits", "0b1", "--verbose"]).is_err());
+}
+
+#[test]
+fn pattern_errors() {
+    assert!(output_states(&options(&["--bits", "1101"])).is_err());
+    assert!(output_states(&options(&["--bits", "0b12"])).is_err());
+    assert!(output_states(&options(&["--bits", "0b"])).is_err());
+    let too_long = format!("0b{}", "1".repeat(128));
+    assert!(output_states(&options(&["--bits", &too_long])).is_err());
+    assert_eq!(
+        Err(morse::describe(MorseError::UnsupportedCode).to_string()),
+        output_states(&options(&["--morse", "~"]))
+    );
+    assert!(output_states(&options(&["--dsl", "#?"])).is_err());
+}
+
+#[test]
+fn empty_patterns() {
+    assert!(run(&options(&["--morse", ""])).is_err());
+    assert!(run(&options(&["--morse", "  "])).is_ok());
+    assert!(run(&options(&["--dsl", ""])).is_err());
+    assert!(run(&options(&["--bits", "0b"])).is_err());
+}
+
+#[test]
+fn usage_example_is_valid() {
+    assert!(USAGE.contains("on 200ms, off 1000ms"));
+    assert_eq!(
+        Ok((0b11, 12)),
+        output_states(&options(&[
+            "--dsl",
+            "on 200ms, off 1000ms",
+            "--tick-ms",
+            "100"
+        ]))
+    );
+}
+
+#[test]
+fn bits_are_played_lsb_first() {
+    assert_eq!("#_##", timeline(&["--bits", "0b1101"]));
+}
+
+#[test]
+fn states_last_update_scale_ticks() {
+    assert_eq!("##__", timeline(&["--dsl", "#_", "--update-scale", "2"]));
+}
+
+#[test]
+fn repetitions() {
+    assert_eq!("#_#_#_", timeline(&["--dsl", "#_", "--repeat", "2"]));
+    assert_eq!("#_", timeline(&["--dsl", "#_", "--repeat", "forever"]));
+    assert_eq!(
+        "#_#_#",
+        timeline(&["--dsl", "#_", "--repeat", "forever", "--ticks", "5"])
+    );
+}
+
+#[test]
+fn durations_in_the_notation() {
+    assert_eq!(
+        "##____",
+        timeline(&["--dsl", "on 20ms, off 40ms", "--tick-ms", "10"])
+    );
+}
+
+#[test]
+fn summary() {
+    let timeline = Timeline {
+        levels: vec![true, true, false, false, false, true, false, false],
+        tick_ms: 0.5,
+    };
+    assert_eq!(4.0, timeline.duration_ms());
+    assert_eq!(37.5, timeline.duty_cycle());
+    assert_eq!(
+        vec![(0, true), (2, false), (5, true), (6, false)],
+        timeline.edges()
+    );
+}
+
+#[test]
+fn vcd() {
+    let timeline = Timeline {
+        levels: vec![true, false, false],
+        tick_ms: 2.0,
+    };
+    assert_eq!(
+        "$timescale 1 us $end\n\
+         $scope module onoff $end\n\
+         $var wire 1 ! pin $end\n\
+         $upscope $end\n\
+         $enddefinitions $end\n\
+         #0\n1!\n\
+         #2000\n0!\n\
+         #6000\n",
+        timeline.to_vcd()
+    );
+}
+
+#[test]
+fn wav() {
+    let timeline = Timeline {
+        levels: vec![true, false],
+        tick_ms: 1.0,
+    };
+    let wav = timeline.to_wav();
+    assert_eq!(b"RIFF", &wav[0..4]);
+    assert_eq!(b"WAVE", &wav[8..12]);
+    assert_eq!(44 + 16, wav.len());
+    assert_eq!(&[0xff; 8], &wav[44..52]);
+    assert_eq!(&[0x00; 8], &wav[52..60]);
+}
+
+#[test]
+fn json() {
+    let timeline = Timeline {
+        levels: vec![true, false],
+        tick_ms: 1.0,
+    };
+    assert_eq!(
+        "{\"output_states\":\"0b1\",\"number_of_output_states\":2,\
+         \"tick_ms\":1,\"duration_ms\":2,\"duty_cycle\":50.0,\"levels\":[1,0]}\n",
+        timeline.to_json(&Pattern::new(0b01, 2, Repeat::Never))
+    );
+}
+
+#[test]
+fn preview_of_a_pattern() {
+    let output = run(&options(&["--bits", "0b01", "--update-scale", "2"])).unwrap();
+    assert_eq!(
+        "timeline:   ##__\n\
+         duration:   4 ms (4 ticks)\n\
+         duty cycle: 50.0 %\n\
+         states:     2 (0b1)\n\
+         encoded:    01 08 02 02 00 01\n",
+        String::from_utf8(output).unwrap()
+    );
+}
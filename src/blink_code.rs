@@ -0,0 +1,276 @@
+//! Numeric blink codes, e.g. error numbers or firmware versions
+//!
+//! Each digit is shown as a group of short blinks: one blink for `1`, two
+//! blinks for `2` and so on. Digits are separated by a longer pause, groups
+//! of digits (e.g. the components of version `1.4.2`) by an even longer
+//! pause. Zero is shown as one long blink or as ten short blinks.
+//!
+//! The sequence always ends with a group gap, so it can be repeated.
+//!
+//! # Example
+//!
+//! ```rust
+//! use on_off_sequence_output::blink_code::*;
+//!
+//! let config = BlinkCodeConfig::default();
+//! let (states, len) = number_to_blink_code(23, &config).unwrap();
+//! assert_eq!(str_to_blink_code("23", &config), Ok((states, len)));
+//!
+//! let mut buffer = [0_u8; 8];
+//! assert_eq!(Ok("23"), blink_code_to_str(states, len, &config, &mut buffer));
+//! ```
+
+use crate::builder::{SequenceBuilder, SequenceTooLong};
+
+/// Blink code conversion errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlinkCodeError {
+    /// Character other than a digit or `.`
+    UnsupportedCode,
+    /// The code has more than 127 states
+    SequenceTooLong,
+    /// Timing is ambiguous, it needs `0 < off < digit_gap < group_gap` and a
+    /// long blink longer than a short blink
+    InvalidConfig,
+    /// The output states are not a blink code of the given configuration
+    MalformedCode,
+    /// The decoded text does not fit into the buffer
+    BufferTooSmall,
+}
+
+/// How a zero digit is shown
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Zero {
+    /// One blink with the given number of on states
+    LongBlink(u16),
+    /// Ten short blinks
+    TenBlinks,
+}
+
+/// Timing of a blink code, all values in number of output states
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlinkCodeConfig {
+    /// On states of a short blink
+    pub on: u16,
+    /// Off states between two blinks of a digit
+    pub off: u16,
+    /// Off states between two digits
+    pub digit_gap: u16,
+    /// Off states between two groups and at the end of the code
+    pub group_gap: u16,
+    /// Representation of the digit zero
+    pub zero: Zero,
+}
+
+impl BlinkCodeConfig {
+    fn is_valid(&self) -> bool {
+        let long_blink_ok = match self.zero {
+            Zero::LongBlink(on) => on > self.on,
+            Zero::TenBlinks => true,
+        };
+        self.on > 0
+            && self.off > 0
+            && self.off < self.digit_gap
+            && self.digit_gap < self.group_gap
+            && long_blink_ok
+    }
+}
+
+impl Default for BlinkCodeConfig {
+    /// Short blinks of one state, digit gap of three states, group gap of
+    /// six states and zero as a long blink of three states
+    fn default() -> Self {
+        Self {
+            on: 1,
+            off: 1,
+            digit_gap: 3,
+            group_gap: 6,
+            zero: Zero::LongBlink(3),
+        }
+    }
+}
+
+impl From<SequenceTooLong> for BlinkCodeError {
+    fn from(_: SequenceTooLong) -> Self {
+        BlinkCodeError::SequenceTooLong
+    }
+}
+
+/// Text of digits to blink code sequence of states
+///
+/// # Arguments
+///
+/// * `input` - Digits, optionally grouped by `.` like `"1.4.2"`
+/// * `config` - Timing of the blink code
+///
+/// # Returns
+///
+/// A result with Ok(tuple) as
+///
+/// * `u128` - a sequence of output states encoded into bits of a u128
+/// * `u16`  - length of the sequence of output states
+///
+/// or Err(BlinkCodeError)
+pub fn str_to_blink_code(
+    input: &str,
+    config: &BlinkCodeConfig,
+) -> Result<(u128, u16), BlinkCodeError> {
+    if !config.is_valid() {
+        return Err(BlinkCodeError::InvalidConfig);
+    }
+    let mut builder = SequenceBuilder::new();
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        let digit = c.to_digit(10).ok_or(BlinkCodeError::UnsupportedCode)?;
+        match (digit, config.zero) {
+            (0, Zero::LongBlink(on)) => builder.push(true, on)?,
+            _ => {
+                let blinks = if digit == 0 { 10 } else { digit };
+                for blink in 0..blinks {
+                    if blink > 0 {
+                        builder.push(false, config.off)?;
+                    }
+                    builder.push(true, config.on)?;
+                }
+            }
+        }
+        let gap = match chars.peek() {
+            None => config.group_gap,
+            Some('.') => {
+                chars.next();
+                if chars.peek().is_none() {
+                    return Err(BlinkCodeError::UnsupportedCode);
+                }
+                config.group_gap
+            }
+            Some(_) => config.digit_gap,
+        };
+        builder.push(false, gap)?;
+    }
+    if builder.len == 0 {
+        return Err(BlinkCodeError::UnsupportedCode);
+    }
+    Ok((builder.sequence, builder.len))
+}
+
+/// Number to blink code sequence of states
+///
+/// # Arguments
+///
+/// * `number` - Number shown digit by digit, most significant digit first
+/// * `config` - Timing of the blink code
+///
+/// # Returns
+///
+/// Same as [`str_to_blink_code`]
+pub fn number_to_blink_code(
+    number: u32,
+    config: &BlinkCodeConfig,
+) -> Result<(u128, u16), BlinkCodeError> {
+    let mut digits = [0_u8; 10];
+    let mut start = digits.len();
+    let mut number = number;
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (number % 10) as u8;
+        number /= 10;
+        if number == 0 {
+            break;
+        }
+    }
+    // only ASCII digits are written
+    let text =
+        core::str::from_utf8(&digits[start..]).map_err(|_| BlinkCodeError::UnsupportedCode)?;
+    str_to_blink_code(text, config)
+}
+
+/// Blink code sequence of states to text of digits
+///
+/// Meant to verify generated codes, e.g. in test fixtures.
+///
+/// # Arguments
+///
+/// * `states` - Output states, processed from lsb to msb
+/// * `len` - Number of output states
+/// * `config` - Timing of the blink code
+/// * `buffer` - Storage for the decoded text
+///
+/// # Returns
+///
+/// A result with Ok(text) of digits grouped by `.`, or Err(BlinkCodeError)
+pub fn blink_code_to_str<'a>(
+    states: u128,
+    len: u16,
+    config: &BlinkCodeConfig,
+    buffer: &'a mut [u8],
+) -> Result<&'a str, BlinkCodeError> {
+    if !config.is_valid() {
+        return Err(BlinkCodeError::InvalidConfig);
+    }
+    let mut written = 0;
+    let mut write = |c: u8, written: &mut usize| -> Result<(), BlinkCodeError> {
+        let slot = buffer
+            .get_mut(*written)
+            .ok_or(BlinkCodeError::BufferTooSmall)?;
+        *slot = c;
+        *written += 1;
+        Ok(())
+    };
+
+    let mut position = 0_u16;
+    let mut blinks = 0_u16;
+    let mut long_blink = false;
+    while position < len {
+        let on = run_length(states, len, position, true);
+        if on == 0 || long_blink {
+            return Err(BlinkCodeError::MalformedCode);
+        }
+        position += on;
+        if on == config.on {
+            blinks += 1;
+        } else if config.zero == Zero::LongBlink(on) && blinks == 0 {
+            long_blink = true;
+        } else {
+            return Err(BlinkCodeError::MalformedCode);
+        }
+
+        let off = run_length(states, len, position, false);
+        position += off;
+        let end_of_digit = position == len || off >= config.digit_gap;
+        if !end_of_digit {
+            if off != config.off {
+                return Err(BlinkCodeError::MalformedCode);
+            }
+            continue;
+        }
+        let digit = match (long_blink, blinks, config.zero) {
+            (true, _, _) => 0,
+            (false, 10, Zero::TenBlinks) => 0,
+            (false, 1..=9, _) => blinks as u8,
+            _ => return Err(BlinkCodeError::MalformedCode),
+        };
+        write(b'0' + digit, &mut written)?;
+        if position < len && off >= config.group_gap {
+            write(b'.', &mut written)?;
+        }
+        blinks = 0;
+        long_blink = false;
+    }
+    if written == 0 {
+        return Err(BlinkCodeError::MalformedCode);
+    }
+    core::str::from_utf8(&buffer[..written]).map_err(|_| BlinkCodeError::MalformedCode)
+}
+
+/// Number of consecutive states of the given level starting at `position`
+fn run_length(states: u128, len: u16, position: u16, on: bool) -> u16 {
+    let mut end = position;
+    while end < len && ((states >> end) & 1 == 1) == on {
+        end += 1;
+    }
+    end - position
+}
+
+#[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
+mod tests;
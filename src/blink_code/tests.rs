@@ -0,0 +1,223 @@
+/// Tests for numeric blink codes
+use super::*;
+use crate::dsl::parse;
+
+fn decode(states: u128, len: u16, config: &BlinkCodeConfig) -> Result<[u8; 16], BlinkCodeError> {
+    let mut buffer = [0_u8; 16];
+    blink_code_to_str(states, len, config, &mut buffer)?;
+    Ok(buffer)
+}
+
+#[test]
+fn encode_single_digit() {
+    let config = BlinkCodeConfig::default();
+    assert_eq!(
+        Ok(parse("#_#_# off 6").unwrap()),
+        str_to_blink_code("3", &config)
+    );
+}
+
+#[test]
+fn encode_two_digits() {
+    let config = BlinkCodeConfig::default();
+    assert_eq!(
+        Ok(parse("#_# ___ #_#_# off 6").unwrap()),
+        str_to_blink_code("23", &config)
+    );
+}
+
+#[test]
+fn encode_groups() {
+    let config = BlinkCodeConfig::default();
+    assert_eq!(
+        Ok(parse("# off 6 #_# off 6").unwrap()),
+        str_to_blink_code("1.2", &config)
+    );
+}
+
+#[test]
+fn encode_zero_as_long_blink() {
+    let config = BlinkCodeConfig::default();
+    assert_eq!(
+        Ok(parse("# ___ ### off 6").unwrap()),
+        str_to_blink_code("10", &config)
+    );
+}
+
+#[test]
+fn encode_zero_as_ten_blinks() {
+    let config = BlinkCodeConfig {
+        zero: Zero::TenBlinks,
+        ..BlinkCodeConfig::default()
+    };
+    assert_eq!(
+        Ok(parse("#_ x9 # off 6").unwrap()),
+        str_to_blink_code("0", &config)
+    );
+}
+
+#[test]
+fn encode_custom_timing() {
+    let config = BlinkCodeConfig {
+        on: 2,
+        off: 2,
+        digit_gap: 4,
+        group_gap: 8,
+        zero: Zero::LongBlink(6),
+    };
+    assert_eq!(
+        Ok(parse("##__## off 8").unwrap()),
+        str_to_blink_code("2", &config)
+    );
+}
+
+#[test]
+fn encode_number() {
+    let config = BlinkCodeConfig::default();
+    assert_eq!(
+        str_to_blink_code("23", &config),
+        number_to_blink_code(23, &config)
+    );
+    assert_eq!(
+        str_to_blink_code("0", &config),
+        number_to_blink_code(0, &config)
+    );
+    assert_eq!(
+        str_to_blink_code("105", &config),
+        number_to_blink_code(105, &config)
+    );
+}
+
+#[test]
+fn encode_errors() {
+    let config = BlinkCodeConfig::default();
+    assert_eq!(
+        Err(BlinkCodeError::UnsupportedCode),
+        str_to_blink_code("", &config)
+    );
+    assert_eq!(
+        Err(BlinkCodeError::UnsupportedCode),
+        str_to_blink_code("1a", &config)
+    );
+    assert_eq!(
+        Err(BlinkCodeError::UnsupportedCode),
+        str_to_blink_code(".1", &config)
+    );
+    assert_eq!(
+        Err(BlinkCodeError::UnsupportedCode),
+        str_to_blink_code("1.", &config)
+    );
+    assert_eq!(
+        Err(BlinkCodeError::UnsupportedCode),
+        str_to_blink_code("1..2", &config)
+    );
+    assert_eq!(
+        Err(BlinkCodeError::SequenceTooLong),
+        str_to_blink_code("9999999", &config)
+    );
+    assert_eq!(
+        Err(BlinkCodeError::SequenceTooLong),
+        number_to_blink_code(u32::MAX, &config)
+    );
+    let config = BlinkCodeConfig {
+        group_gap: u16::MAX,
+        ..BlinkCodeConfig::default()
+    };
+    assert_eq!(
+        Err(BlinkCodeError::SequenceTooLong),
+        str_to_blink_code("1", &config)
+    );
+}
+
+#[test]
+fn invalid_config() {
+    let ambiguous_gaps = BlinkCodeConfig {
+        digit_gap: 6,
+        ..BlinkCodeConfig::default()
+    };
+    let short_long_blink = BlinkCodeConfig {
+        zero: Zero::LongBlink(1),
+        ..BlinkCodeConfig::default()
+    };
+    for config in [ambiguous_gaps, short_long_blink].iter() {
+        assert_eq!(
+            Err(BlinkCodeError::InvalidConfig),
+            str_to_blink_code("1", config)
+        );
+        assert_eq!(Err(BlinkCodeError::InvalidConfig), decode(0b1, 7, config));
+    }
+}
+
+#[test]
+fn round_trip() {
+    let default = BlinkCodeConfig::default();
+    let ten_blinks = BlinkCodeConfig {
+        zero: Zero::TenBlinks,
+        ..default
+    };
+    let slow = BlinkCodeConfig {
+        on: 2,
+        off: 2,
+        digit_gap: 5,
+        group_gap: 9,
+        zero: Zero::LongBlink(5),
+    };
+    for config in [default, ten_blinks, slow].iter() {
+        for text in ["1", "9", "0", "23", "1.4.2", "10.0", "7.77"].iter() {
+            let (states, len) = str_to_blink_code(text, config).unwrap();
+            let mut buffer = [0_u8; 16];
+            assert_eq!(
+                Ok(*text),
+                blink_code_to_str(states, len, config, &mut buffer)
+            );
+        }
+    }
+}
+
+#[test]
+fn decode_without_final_gap() {
+    let config = BlinkCodeConfig::default();
+    let (states, len) = parse("#_# ___ #_#_#").unwrap();
+    let mut buffer = [0_u8; 4];
+    assert_eq!(
+        Ok("23"),
+        blink_code_to_str(states, len, &config, &mut buffer)
+    );
+}
+
+#[test]
+fn decode_malformed() {
+    let config = BlinkCodeConfig::default();
+    // leading off state
+    assert_eq!(Err(BlinkCodeError::MalformedCode), decode(0b10, 4, &config));
+    // off between blink off and digit gap
+    assert_eq!(
+        Err(BlinkCodeError::MalformedCode),
+        decode(0b1001, 8, &config)
+    );
+    // blink of wrong length
+    assert_eq!(Err(BlinkCodeError::MalformedCode), decode(0b11, 8, &config));
+    // long blink within a digit
+    assert_eq!(
+        Err(BlinkCodeError::MalformedCode),
+        decode(0b11101, 10, &config)
+    );
+    // more than nine blinks
+    assert_eq!(
+        Err(BlinkCodeError::MalformedCode),
+        decode(0b1010101010101010101, 25, &config)
+    );
+    // nothing to decode
+    assert_eq!(Err(BlinkCodeError::MalformedCode), decode(0, 0, &config));
+}
+
+#[test]
+fn decode_buffer_too_small() {
+    let config = BlinkCodeConfig::default();
+    let (states, len) = str_to_blink_code("1.4.2", &config).unwrap();
+    let mut buffer = [0_u8; 4];
+    assert_eq!(
+        Err(BlinkCodeError::BufferTooSmall),
+        blink_code_to_str(states, len, &config, &mut buffer)
+    );
+}
@@ -0,0 +1,73 @@
+//! Limits and construction of output state sequences shared by the outputs
+//! and the encoders
+
+/// Maximum number of output states of a sequence
+pub(crate) const MAX_NUMBER_OF_STATES: u16 = 127;
+
+/// Invalid number of output states
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum NumberOfStatesError {
+    /// Zero output states
+    Zero,
+    /// More than [`MAX_NUMBER_OF_STATES`]
+    TooMany,
+}
+
+/// Check a number of output states, 1 up to [`MAX_NUMBER_OF_STATES`]
+pub(crate) const fn check_number_of_states(
+    number_of_output_states: u16,
+) -> Result<(), NumberOfStatesError> {
+    if number_of_output_states == 0 {
+        Err(NumberOfStatesError::Zero)
+    } else if number_of_output_states > MAX_NUMBER_OF_STATES {
+        Err(NumberOfStatesError::TooMany)
+    } else {
+        Ok(())
+    }
+}
+
+/// Panics on an invalid number of output states, as `set()` does
+pub(crate) const fn assert_number_of_states(number_of_output_states: u16) {
+    match check_number_of_states(number_of_output_states) {
+        Ok(()) => {}
+        Err(NumberOfStatesError::Zero) => panic!("Zero output states do not make sense"),
+        Err(NumberOfStatesError::TooMany) => panic!("Must be less than 128 output states"),
+    }
+}
+
+/// The sequence would exceed [`MAX_NUMBER_OF_STATES`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct SequenceTooLong;
+
+/// Collects output states from lsb to msb
+pub(crate) struct SequenceBuilder {
+    pub(crate) sequence: u128,
+    pub(crate) len: u16,
+}
+
+impl SequenceBuilder {
+    pub(crate) const fn new() -> Self {
+        Self {
+            sequence: 0,
+            len: 0,
+        }
+    }
+
+    /// Appends `count` states of the same level
+    pub(crate) fn push(&mut self, on: bool, count: u16) -> Result<(), SequenceTooLong> {
+        // len never exceeds the maximum, so the subtraction cannot underflow
+        if count > MAX_NUMBER_OF_STATES - self.len {
+            return Err(SequenceTooLong);
+        }
+        if on {
+            self.sequence |= ((1_u128 << count) - 1) << self.len;
+        }
+        self.len += count;
+        Ok(())
+    }
+
+    /// The level of the last state, off if there is none
+    pub(crate) fn last(&self) -> bool {
+        self.len > 0 && (self.sequence >> (self.len - 1)) & 1 == 1
+    }
+}
@@ -0,0 +1,477 @@
+//! Line protocol to drive outputs over a serial line
+//!
+//! Commands are typed into a console, e.g. on a UART, one command per line.
+//! A [`LineReader`] collects the received bytes into lines, a [`Dispatcher`]
+//! executes the commands on a number of outputs and writes a response.
+//!
+//! # Commands
+//!
+//! Outputs are addressed by their index, the output index is optional and
+//! defaults to output 0.
+//!
+//! | command                                 | effect                              |
+//! |-----------------------------------------|-------------------------------------|
+//! | `blink [<output>] <states> [<repeat>]`  | `set()`, states as `0b1101` literal (lsb first, every digit is a state) or in the notation of the [`dsl`](crate::dsl) module without blanks |
+//! | `morse [<output>] <text> [<repeat>]`    | `set_morse()`                       |
+//! | `pause [<output>]`                      | the output keeps its current state  |
+//! | `resume [<output>]`                     | a paused output continues           |
+//! | `stop [<output>]`                       | the output is switched off          |
+//! | `status [<output>]`                     | state of one or of all outputs      |
+//!
+//! `<repeat>` is one of `once` (default), `forever` or `times <n>`.
+//!
+//! Every command is answered with `ok`, status lines or `error: ...`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use on_off_sequence_output::command::{Dispatcher, LineReader};
+//! use on_off_sequence_output::prelude::*;
+//!
+//! let mut outputs = [OnOffSequenceOutput::new(led0, 500), OnOffSequenceOutput::new(led1, 500)];
+//! let mut dispatcher = Dispatcher::new(&mut outputs);
+//! let mut reader = LineReader::<64>::new();
+//! loop {
+//!     if let Ok(byte) = serial.read() {
+//!         match reader.push(byte) {
+//!             Some(Ok(line)) => dispatcher.handle_line(line, &mut serial).unwrap(),
+//!             Some(Err(error)) => dispatcher.handle_error(error, &mut serial).unwrap(),
+//!             None => {}
+//!         }
+//!     }
+//!     dispatcher.update().unwrap();
+//!     wait(1.ms());
+//! }
+//! ```
+
+use crate::builder::{check_number_of_states, MAX_NUMBER_OF_STATES};
+use crate::morse::str_to_morse;
+use crate::{OnOffSequenceOutput, OutputUpdate, Repeat};
+use core::fmt::Write;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Maximum number of outputs of a dispatcher
+pub const MAX_NUMBER_OF_OUTPUTS: usize = 32;
+
+/// Command errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CommandError {
+    /// The line exceeds the buffer of the line reader
+    LineTooLong,
+    /// The line is not valid UTF-8
+    InvalidText,
+    /// The command is not known
+    UnknownCommand,
+    /// An argument is missing
+    MissingArgument,
+    /// An argument can not be parsed
+    InvalidArgument,
+    /// More arguments than expected
+    UnexpectedArgument,
+    /// The output index is out of range
+    NoSuchOutput,
+}
+
+/// Describe a command error
+pub const fn describe(error: CommandError) -> &'static str {
+    match error {
+        CommandError::LineTooLong => "line too long",
+        CommandError::InvalidText => "line is not valid text",
+        CommandError::UnknownCommand => "unknown command",
+        CommandError::MissingArgument => "missing argument",
+        CommandError::InvalidArgument => "invalid argument",
+        CommandError::UnexpectedArgument => "unexpected argument",
+        CommandError::NoSuchOutput => "no such output",
+    }
+}
+
+/// A parsed command
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command<'a> {
+    /// Set new output states
+    Blink {
+        output: usize,
+        output_states: u128,
+        number_of_output_states: u16,
+        repeat: Repeat,
+    },
+    /// Set a morse code
+    Morse {
+        output: usize,
+        text: &'a str,
+        repeat: Repeat,
+    },
+    /// Keep the current state
+    Pause { output: usize },
+    /// Continue a paused output
+    Resume { output: usize },
+    /// Switch the output off
+    Stop { output: usize },
+    /// Query the state of one or of all outputs
+    Status { output: Option<usize> },
+}
+
+/// Parse a command line
+pub fn parse(line: &str) -> Result<Command<'_>, CommandError> {
+    let mut words = line.split_ascii_whitespace();
+    let command = words.next().ok_or(CommandError::UnknownCommand)?;
+    let mut arguments = Arguments::new(line, words);
+    let command = match command {
+        "blink" => {
+            let repeat = arguments.repeat()?;
+            let output = arguments.output_if_followed()?.unwrap_or(0);
+            let states = arguments.next().ok_or(CommandError::MissingArgument)?;
+            let (output_states, number_of_output_states) = parse_states(states)?;
+            Command::Blink {
+                output,
+                output_states,
+                number_of_output_states,
+                repeat,
+            }
+        }
+        "morse" => {
+            let repeat = arguments.repeat()?;
+            let output = arguments.output_if_followed()?.unwrap_or(0);
+            let text = arguments.rest().ok_or(CommandError::MissingArgument)?;
+            Command::Morse {
+                output,
+                text,
+                repeat,
+            }
+        }
+        "pause" => Command::Pause {
+            output: arguments.output()?.unwrap_or(0),
+        },
+        "resume" => Command::Resume {
+            output: arguments.output()?.unwrap_or(0),
+        },
+        "stop" => Command::Stop {
+            output: arguments.output()?.unwrap_or(0),
+        },
+        "status" => Command::Status {
+            output: arguments.output()?,
+        },
+        _ => return Err(CommandError::UnknownCommand),
+    };
+    if arguments.next().is_some() {
+        return Err(CommandError::UnexpectedArgument);
+    }
+    Ok(command)
+}
+
+/// Parse output states given as `0b` literal or in dsl notation
+///
+/// # Arguments
+///
+/// * `word` - A `0b` literal like `0b1101`, lsb first and every digit a
+///   state, `_` may group digits; any other text in the notation of the
+///   [`dsl`](crate::dsl) module
+///
+/// # Returns
+///
+/// A result with Ok(tuple) of output states and number of output states,
+/// or Err(CommandError::InvalidArgument)
+pub fn parse_states(word: &str) -> Result<(u128, u16), CommandError> {
+    if let Some(digits) = word.strip_prefix("0b") {
+        let mut output_states = 0u128;
+        let mut number_of_output_states = 0u16;
+        for digit in digits.bytes().filter(|&digit| digit != b'_') {
+            let bit = match digit {
+                b'0' => 0,
+                b'1' => 1,
+                _ => return Err(CommandError::InvalidArgument),
+            };
+            number_of_output_states += 1;
+            if number_of_output_states > MAX_NUMBER_OF_STATES {
+                return Err(CommandError::InvalidArgument);
+            }
+            output_states = (output_states << 1) | bit;
+        }
+        check_number_of_states(number_of_output_states)
+            .map_err(|_| CommandError::InvalidArgument)?;
+        Ok((output_states, number_of_output_states))
+    } else {
+        crate::dsl::parse(word).map_err(|_| CommandError::InvalidArgument)
+    }
+}
+
+/// The arguments of a command line
+struct Arguments<'a, I: Iterator<Item = &'a str>> {
+    line: &'a str,
+    words: core::iter::Peekable<I>,
+    /// Number of trailing words of the repeat clause
+    repeat_words: usize,
+    /// Number of words left
+    len: usize,
+}
+
+impl<'a, I: Iterator<Item = &'a str> + Clone> Arguments<'a, I> {
+    fn new(line: &'a str, words: I) -> Self {
+        let len = words.clone().count();
+        Self {
+            line,
+            words: words.peekable(),
+            repeat_words: 0,
+            len,
+        }
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.len == self.repeat_words {
+            return None;
+        }
+        self.len -= 1;
+        self.words.next()
+    }
+
+    /// Parses and strips the trailing repeat clause
+    fn repeat(&mut self) -> Result<Repeat, CommandError> {
+        let mut last = self.words.clone().skip(self.len.saturating_sub(2));
+        let (second_last, last) = match (last.next(), last.next()) {
+            (Some(second_last), Some(last)) => (Some(second_last), last),
+            (Some(last), None) => (None, last),
+            _ => return Ok(Repeat::Never),
+        };
+        let repeat = match (second_last, last) {
+            (_, "once") => Repeat::Never,
+            (_, "forever") => Repeat::Forever,
+            (Some("times"), count) => {
+                self.repeat_words = 1;
+                Repeat::Times(count.parse().map_err(|_| CommandError::InvalidArgument)?)
+            }
+            (_, "times") => return Err(CommandError::MissingArgument),
+            _ => return Ok(Repeat::Never),
+        };
+        self.repeat_words += 1;
+        Ok(repeat)
+    }
+
+    /// Takes the output index, if there are further arguments
+    fn output_if_followed(&mut self) -> Result<Option<usize>, CommandError> {
+        if self.len - self.repeat_words < 2 {
+            return Ok(None);
+        }
+        match self.words.peek() {
+            Some(word) if word.bytes().all(|byte| byte.is_ascii_digit()) => self.output(),
+            _ => Ok(None),
+        }
+    }
+
+    /// Takes the output index
+    fn output(&mut self) -> Result<Option<usize>, CommandError> {
+        match self.next() {
+            Some(word) => word
+                .parse()
+                .map(Some)
+                .map_err(|_| CommandError::InvalidArgument),
+            None => Ok(None),
+        }
+    }
+
+    /// Takes all words up to the repeat clause as they are in the line
+    fn rest(&mut self) -> Option<&'a str> {
+        let first = self.next()?;
+        let mut last = first;
+        while let Some(word) = self.next() {
+            last = word;
+        }
+        // the words are slices of the line
+        let start = first.as_ptr() as usize - self.line.as_ptr() as usize;
+        let end = last.as_ptr() as usize - self.line.as_ptr() as usize + last.len();
+        Some(&self.line[start..end])
+    }
+}
+
+/// Collects received bytes into lines
+pub struct LineReader<const N: usize> {
+    buffer: [u8; N],
+    len: usize,
+    /// The current line exceeded the buffer
+    overflow: bool,
+}
+
+impl<const N: usize> LineReader<N> {
+    /// Creates an empty line reader
+    pub const fn new() -> Self {
+        Self {
+            buffer: [0; N],
+            len: 0,
+            overflow: false,
+        }
+    }
+
+    /// Adds a received byte
+    ///
+    /// # Returns
+    ///
+    /// * Some(Ok(line)) - if the byte completed a non-empty line; carriage
+    ///   return and line feed end a line
+    /// * Some(Err(CommandError)) - if the completed line is too long or not
+    ///   text
+    /// * None - otherwise
+    pub fn push(&mut self, byte: u8) -> Option<Result<&str, CommandError>> {
+        if byte != b'\n' && byte != b'\r' {
+            if self.len < N {
+                self.buffer[self.len] = byte;
+                self.len += 1;
+            } else {
+                self.overflow = true;
+            }
+            return None;
+        }
+        let len = core::mem::replace(&mut self.len, 0);
+        if core::mem::replace(&mut self.overflow, false) {
+            return Some(Err(CommandError::LineTooLong));
+        }
+        if len == 0 {
+            return None;
+        }
+        Some(core::str::from_utf8(&self.buffer[..len]).map_err(|_| CommandError::InvalidText))
+    }
+}
+
+impl<const N: usize> Default for LineReader<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Executes commands on a number of outputs
+pub struct Dispatcher<'a, T: OutputPin> {
+    outputs: &'a mut [OnOffSequenceOutput<T>],
+    /// Bit n is set if output n is paused
+    paused: u32,
+}
+
+impl<'a, T: OutputPin> Dispatcher<'a, T> {
+    /// Creates a dispatcher for the outputs, addressed by their index
+    ///
+    /// # Panics
+    ///
+    /// If there are more than 32 outputs
+    pub fn new(outputs: &'a mut [OnOffSequenceOutput<T>]) -> Self {
+        if outputs.len() > MAX_NUMBER_OF_OUTPUTS {
+            panic!("Must be at most 32 outputs");
+        };
+        Self { outputs, paused: 0 }
+    }
+
+    /// Executes a command, writes status lines to the response
+    pub fn execute<W: Write>(
+        &mut self,
+        command: Command<'_>,
+        response: &mut W,
+    ) -> Result<(), CommandError> {
+        match command {
+            Command::Blink {
+                output,
+                output_states,
+                number_of_output_states,
+                repeat,
+            } => {
+                self.output(output)?
+                    .set(output_states, number_of_output_states, repeat);
+                self.paused &= !(1 << output);
+            }
+            Command::Morse {
+                output,
+                text,
+                repeat,
+            } => {
+                let (output_states, number_of_output_states) =
+                    str_to_morse(text).map_err(|_| CommandError::InvalidArgument)?;
+                self.output(output)?
+                    .set(output_states, number_of_output_states, repeat);
+                self.paused &= !(1 << output);
+            }
+            Command::Pause { output } => {
+                self.output(output)?;
+                self.paused |= 1 << output;
+            }
+            Command::Resume { output } => {
+                self.output(output)?;
+                self.paused &= !(1 << output);
+            }
+            Command::Stop { output } => {
+                self.output(output)?.set(0, 1, Repeat::Never);
+                self.paused &= !(1 << output);
+            }
+            Command::Status {
+                output: Some(output),
+            } => {
+                self.output(output)?;
+                self.write_status(output, response);
+            }
+            Command::Status { output: None } => {
+                for output in 0..self.outputs.len() {
+                    self.write_status(output, response);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses and executes a command line, writes the response
+    ///
+    /// Errors of the command are reported in the response.
+    pub fn handle_line<W: Write>(&mut self, line: &str, response: &mut W) -> core::fmt::Result {
+        match parse(line).and_then(|command| self.execute(command, response)) {
+            Ok(()) => response.write_str("ok\r\n"),
+            Err(error) => write!(response, "error: {}\r\n", describe(error)),
+        }
+    }
+
+    /// Reports an error of the line reader in the response
+    pub fn handle_error<W: Write>(
+        &self,
+        error: CommandError,
+        response: &mut W,
+    ) -> core::fmt::Result {
+        write!(response, "error: {}\r\n", describe(error))
+    }
+
+    /// Splits back into the outputs
+    pub fn release(self) -> &'a mut [OnOffSequenceOutput<T>] {
+        self.outputs
+    }
+
+    fn output(&mut self, output: usize) -> Result<&mut OnOffSequenceOutput<T>, CommandError> {
+        self.outputs
+            .get_mut(output)
+            .ok_or(CommandError::NoSuchOutput)
+    }
+
+    fn write_status<W: Write>(&self, output: usize, response: &mut W) {
+        let state = if self.paused & (1 << output) != 0 {
+            "paused"
+        } else if self.outputs[output].is_completed() {
+            "completed"
+        } else {
+            "running"
+        };
+        // a response channel which can not take the status has to drop it
+        let _ = write!(response, "{} {}\r\n", output, state);
+    }
+}
+
+impl<T: OutputPin> OutputUpdate for Dispatcher<'_, T> {
+    type Error = T::Error;
+
+    /// Updates all outputs which are not paused
+    ///
+    /// # Returns
+    ///
+    /// true if all outputs which are not paused are completed
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        let mut completed = true;
+        for (index, output) in self.outputs.iter_mut().enumerate() {
+            if self.paused & (1 << index) == 0 {
+                completed &= output.update()?;
+            }
+        }
+        Ok(completed)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,264 @@
+/// Tests for the command line protocol
+use super::*;
+use crate::tests::mocks::*;
+extern crate std;
+use std::string::String;
+
+/// Feeds an in-memory byte stream through a line reader into the
+/// dispatcher, collects the responses
+fn run<T: OutputPin>(dispatcher: &mut Dispatcher<'_, T>, input: &[u8]) -> String {
+    let mut reader = LineReader::<32>::new();
+    let mut response = String::new();
+    for &byte in input {
+        match reader.push(byte) {
+            Some(Ok(line)) => dispatcher.handle_line(line, &mut response).unwrap(),
+            Some(Err(error)) => dispatcher.handle_error(error, &mut response).unwrap(),
+            None => {}
+        }
+    }
+    response
+}
+
+mod parser {
+    use super::*;
+
+    #[test]
+    fn blink() {
+        assert_eq!(
+            Ok(Command::Blink {
+                output: 3,
+                output_states: 0b1101,
+                number_of_output_states: 4,
+                repeat: Repeat::Times(5)
+            }),
+            parse("blink 3 0b1101 times 5")
+        );
+    }
+
+    #[test]
+    fn blink_defaults() {
+        assert_eq!(
+            Ok(Command::Blink {
+                output: 0,
+                output_states: 0b0010,
+                number_of_output_states: 4,
+                repeat: Repeat::Never
+            }),
+            parse("blink 0b0010")
+        );
+    }
+
+    #[test]
+    fn blink_dsl() {
+        assert_eq!(
+            Ok(Command::Blink {
+                output: 1,
+                output_states: 0b1101,
+                number_of_output_states: 4,
+                repeat: Repeat::Forever
+            }),
+            parse("  blink 1 #_## forever ")
+        );
+    }
+
+    #[test]
+    fn morse() {
+        assert_eq!(
+            Ok(Command::Morse {
+                output: 0,
+                text: "SOS",
+                repeat: Repeat::Forever
+            }),
+            parse("morse SOS forever")
+        );
+        assert_eq!(
+            Ok(Command::Morse {
+                output: 2,
+                text: "SOS  HELP",
+                repeat: Repeat::Never
+            }),
+            parse("morse 2 SOS  HELP once")
+        );
+    }
+
+    #[test]
+    fn morse_of_a_number() {
+        assert_eq!(
+            Ok(Command::Morse {
+                output: 0,
+                text: "42",
+                repeat: Repeat::Never
+            }),
+            parse("morse 42")
+        );
+    }
+
+    #[test]
+    fn control() {
+        assert_eq!(Ok(Command::Pause { output: 0 }), parse("pause"));
+        assert_eq!(Ok(Command::Resume { output: 1 }), parse("resume 1"));
+        assert_eq!(Ok(Command::Stop { output: 2 }), parse("stop 2"));
+        assert_eq!(Ok(Command::Status { output: None }), parse("status"));
+        assert_eq!(Ok(Command::Status { output: Some(0) }), parse("status 0"));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Err(CommandError::UnknownCommand), parse(""));
+        assert_eq!(Err(CommandError::UnknownCommand), parse("flash 0b1"));
+        assert_eq!(Err(CommandError::MissingArgument), parse("blink"));
+        assert_eq!(Err(CommandError::MissingArgument), parse("blink forever"));
+        assert_eq!(Err(CommandError::MissingArgument), parse("blink 0b1 times"));
+        assert_eq!(Err(CommandError::InvalidArgument), parse("blink 0b12"));
+        assert_eq!(Err(CommandError::InvalidArgument), parse("blink 0b"));
+        assert_eq!(
+            Err(CommandError::InvalidArgument),
+            parse("blink 0b1 times x")
+        );
+        assert_eq!(Err(CommandError::InvalidArgument), parse("pause x"));
+        assert_eq!(
+            Err(CommandError::UnexpectedArgument),
+            parse("blink 0b1 0b1 0b1")
+        );
+        assert_eq!(Err(CommandError::UnexpectedArgument), parse("stop 1 2"));
+        assert_eq!(Err(CommandError::MissingArgument), parse("morse"));
+    }
+
+    #[test]
+    fn oversized_counts() {
+        assert_eq!(
+            Err(CommandError::InvalidArgument),
+            parse("blink ##x4294967295")
+        );
+        assert_eq!(
+            Err(CommandError::InvalidArgument),
+            parse("blink #on4294967295")
+        );
+    }
+}
+
+mod line_reader {
+    use super::*;
+
+    #[test]
+    fn lines() {
+        let mut reader = LineReader::<8>::new();
+        for &byte in b"stop" {
+            assert_eq!(None, reader.push(byte));
+        }
+        assert_eq!(Some(Ok("stop")), reader.push(b'\r'));
+        assert_eq!(None, reader.push(b'\n'), "empty line");
+        assert_eq!(None, reader.push(b'x'));
+        assert_eq!(Some(Ok("x")), reader.push(b'\n'));
+    }
+
+    #[test]
+    fn line_too_long() {
+        let mut reader = LineReader::<2>::new();
+        for &byte in b"status" {
+            assert_eq!(None, reader.push(byte));
+        }
+        assert_eq!(Some(Err(CommandError::LineTooLong)), reader.push(b'\n'));
+        assert_eq!(None, reader.push(b'x'));
+        assert_eq!(Some(Ok("x")), reader.push(b'\n'));
+    }
+
+    #[test]
+    fn invalid_text() {
+        let mut reader = LineReader::<2>::new();
+        assert_eq!(None, reader.push(0xff));
+        assert_eq!(Some(Err(CommandError::InvalidText)), reader.push(b'\n'));
+    }
+}
+
+mod dispatcher {
+    use super::*;
+
+    #[test]
+    fn blink_and_status() -> Result<(), MockedOutputPinError> {
+        let mut outputs = [
+            OnOffSequenceOutput::new(MockedOutputPin::expected(2, 0b01_u128), 1),
+            OnOffSequenceOutput::new(MockedOutputPin::expected(0, 0b0_u128), 1),
+        ];
+        let mut dispatcher = Dispatcher::new(&mut outputs);
+        assert_eq!(
+            "ok\r\nok\r\n",
+            run(&mut dispatcher, b"blink 0 0b01\r\npause 1\r\n")
+        );
+        assert!(!dispatcher.update()?);
+        assert_eq!("0 running\r\nok\r\n", run(&mut dispatcher, b"status 0\n"));
+        assert!(dispatcher.update()?);
+        assert_eq!(
+            "0 completed\r\n1 paused\r\nok\r\n",
+            run(&mut dispatcher, b"status\n")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn morse_pause_resume() -> Result<(), MockedOutputPinError> {
+        // "E" is a single dot
+        let mut outputs = [OnOffSequenceOutput::new(
+            MockedOutputPin::expected(2, 0b01_u128),
+            1,
+        )];
+        let mut dispatcher = Dispatcher::new(&mut outputs);
+        assert_eq!("ok\r\n", run(&mut dispatcher, b"morse E\n"));
+        dispatcher.update()?;
+        assert_eq!("ok\r\n", run(&mut dispatcher, b"pause\n"));
+        assert!(dispatcher.update()?, "nothing to do while paused");
+        assert_eq!("ok\r\n", run(&mut dispatcher, b"resume\n"));
+        dispatcher.update()?;
+        Ok(())
+    }
+
+    #[test]
+    fn stop() -> Result<(), MockedOutputPinError> {
+        let mut outputs = [OnOffSequenceOutput::new(
+            MockedOutputPin::expected(2, 0b01_u128),
+            1,
+        )];
+        let mut dispatcher = Dispatcher::new(&mut outputs);
+        assert_eq!("ok\r\n", run(&mut dispatcher, b"blink #### forever\n"));
+        dispatcher.update()?;
+        assert_eq!("ok\r\n", run(&mut dispatcher, b"stop\n"));
+        assert!(dispatcher.update()?);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        let mut outputs = [OnOffSequenceOutput::new(
+            MockedOutputPin::expected(0, 0b0_u128),
+            1,
+        )];
+        let mut dispatcher = Dispatcher::new(&mut outputs);
+        assert_eq!(
+            "error: no such output\r\n\
+             error: unknown command\r\n\
+             error: invalid argument\r\n\
+             error: line too long\r\n",
+            run(
+                &mut dispatcher,
+                b"stop 1\nhelp\nmorse ~\nmorse 0123456789012345678901234567890123456789\n"
+            )
+        );
+    }
+
+    #[test]
+    fn oversized_counts() {
+        let mut outputs = [OnOffSequenceOutput::new(
+            MockedOutputPin::expected(0, 0b0_u128),
+            1,
+        )];
+        let mut dispatcher = Dispatcher::new(&mut outputs);
+        assert_eq!(
+            "error: invalid argument\r\n\
+             error: invalid argument\r\n",
+            run(
+                &mut dispatcher,
+                b"blink ##x4294967295\nblink #on4294967295 forever\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Text notation for on/off sequences
+//!
+//! Writing `0b11111111001111` and counting bits is error-prone, in particular
+//! since the bits are played from lsb to msb, i.e. reversed to how they
+//! read. This module parses a small text notation instead. The first
+//! character of the text is played first.
+//!
+//! # Notation
+//!
+//! * `#` or `1` - one state on
+//! * `_`, `-` or `0` - one state off
+//! * `on N` / `off N` - *N* states on or off
+//! * `on Nms` / `off Nms` - on or off for *N* milliseconds; requires a state
+//!   period (see [`parse_timed`])
+//! * `xN` - repeat everything written so far *N* times in total; at least
+//!   one state has to be written before
+//! * blanks and commas - ignored, may be used for grouping
+//!
+//! # Example
+//!
+//! ```rust
+//! use on_off_sequence_output::dsl::*;
+//!
+//! assert_eq!(Ok((0b_0001101_u128, 7_u16)), parse("#_##___"));
+//! assert_eq!(Ok((0b_011_011_u128, 6_u16)), parse("on 2, off 1 x2"));
+//! assert_eq!(Ok((0b_0_11_u128, 3_u16)), parse_timed("on 200ms, off 100ms", 100));
+//! ```
+//!
+//! Fixed patterns are better parsed at compile time, see [`sequence!`].
+//!
+//! [`sequence!`]: ../macro.sequence.html
+
+use crate::builder::MAX_NUMBER_OF_STATES;
+
+/// Pattern notation parsing errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DslError {
+    /// A character that is not part of the notation
+    UnexpectedCharacter,
+    /// `on`, `off` or `x` is not followed by a number
+    MissingCount,
+    /// A count is zero or does not fit into a `u32`
+    InvalidCount,
+    /// A duration in `ms` is used but no state period is given
+    MissingStatePeriod,
+    /// The sequence has more than 127 states
+    SequenceTooLong,
+    /// The text does not contain a single state
+    Empty,
+    /// `x` is not preceded by a state to repeat
+    NothingToRepeat,
+}
+
+/// Parse a pattern text into a sequence of output states
+///
+/// # Arguments
+///
+/// * `text` - Pattern in the notation described at the [module](index.html)
+///   level; durations in `ms` are not accepted.
+///
+/// # Returns
+///
+/// A result with Ok(tuple) as
+///
+/// * `u128` - a sequence of output states encoded into bits of a u128
+/// * `u16`  - length of the sequence of output states
+///
+/// or Err(DslError)
+pub const fn parse(text: &str) -> Result<(u128, u16), DslError> {
+    parse_timed(text, 0)
+}
+
+/// Parse a pattern text that may contain durations in milliseconds
+///
+/// # Arguments
+///
+/// * `text` - Pattern in the notation described at the [module](index.html)
+///   level
+/// * `state_period_ms` - Duration of one output state in milliseconds, i.e.
+///   update period times update scale. Durations are rounded to the nearest
+///   number of states, but are at least one state long. Zero rejects
+///   durations in `ms`.
+///
+/// # Returns
+///
+/// Same as [`parse`]
+pub const fn parse_timed(text: &str, state_period_ms: u32) -> Result<(u128, u16), DslError> {
+    let bytes = text.as_bytes();
+    let mut sequence = 0_u128;
+    let mut len = 0_u16;
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b' ' | b'\t' | b'\r' | b'\n' | b',' => index += 1,
+            b'#' | b'1' | b'_' | b'-' | b'0' => {
+                if len >= MAX_NUMBER_OF_STATES {
+                    return Err(DslError::SequenceTooLong);
+                }
+                if bytes[index] == b'#' || bytes[index] == b'1' {
+                    sequence |= 1 << len;
+                }
+                len += 1;
+                index += 1;
+            }
+            b'o' => {
+                let (on, keyword_len) = if starts_with(bytes, index, b"off") {
+                    (false, 3)
+                } else if starts_with(bytes, index, b"on") {
+                    (true, 2)
+                } else {
+                    return Err(DslError::UnexpectedCharacter);
+                };
+                let (count, next) = match parse_count(bytes, index + keyword_len) {
+                    Ok(c) => c,
+                    Err(e) => return Err(e),
+                };
+                let states = match count {
+                    Count::States(n) => n,
+                    Count::Milliseconds(ms) => {
+                        if state_period_ms == 0 {
+                            return Err(DslError::MissingStatePeriod);
+                        }
+                        let n = (ms as u64 + state_period_ms as u64 / 2) / state_period_ms as u64;
+                        if n == 0 {
+                            1
+                        } else if n > u32::MAX as u64 {
+                            u32::MAX
+                        } else {
+                            n as u32
+                        }
+                    }
+                };
+                if states > (MAX_NUMBER_OF_STATES - len) as u32 {
+                    return Err(DslError::SequenceTooLong);
+                }
+                if on {
+                    sequence |= ((1_u128 << states) - 1) << len;
+                }
+                len += states as u16;
+                index = next;
+            }
+            b'x' => {
+                if len == 0 {
+                    return Err(DslError::NothingToRepeat);
+                }
+                let (count, next) = match parse_count(bytes, index + 1) {
+                    Ok(c) => c,
+                    Err(e) => return Err(e),
+                };
+                let times = match count {
+                    Count::States(n) => n,
+                    Count::Milliseconds(_) => return Err(DslError::UnexpectedCharacter),
+                };
+                // bounded before multiplying
+                if times > (MAX_NUMBER_OF_STATES / len) as u32 {
+                    return Err(DslError::SequenceTooLong);
+                }
+                let pattern = sequence;
+                let mut time = 1;
+                while time < times {
+                    sequence |= pattern << (len as u32 * time);
+                    time += 1;
+                }
+                len *= times as u16;
+                index = next;
+            }
+            _ => return Err(DslError::UnexpectedCharacter),
+        }
+    }
+    if len == 0 {
+        return Err(DslError::Empty);
+    }
+    Ok((sequence, len))
+}
+
+/// Count following `on`, `off` or `x`
+enum Count {
+    States(u32),
+    Milliseconds(u32),
+}
+
+/// check if `prefix` is found in `bytes` at `index`
+const fn starts_with(bytes: &[u8], index: usize, prefix: &[u8]) -> bool {
+    if index + prefix.len() > bytes.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < prefix.len() {
+        if bytes[index + i] != prefix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Parse a non-zero decimal count with optional `ms` unit, blanks before the
+/// number are skipped
+///
+/// Returns the count and the index after it
+const fn parse_count(bytes: &[u8], index: usize) -> Result<(Count, usize), DslError> {
+    let mut index = index;
+    while index < bytes.len() && (bytes[index] == b' ' || bytes[index] == b'\t') {
+        index += 1;
+    }
+    let start = index;
+    let mut value = 0_u32;
+    while index < bytes.len() && bytes[index].is_ascii_digit() {
+        value = match value.checked_mul(10) {
+            Some(v) => match v.checked_add((bytes[index] - b'0') as u32) {
+                Some(v) => v,
+                None => return Err(DslError::InvalidCount),
+            },
+            None => return Err(DslError::InvalidCount),
+        };
+        index += 1;
+    }
+    if index == start {
+        return Err(DslError::MissingCount);
+    }
+    if value == 0 {
+        return Err(DslError::InvalidCount);
+    }
+    if starts_with(bytes, index, b"ms") {
+        return Ok((Count::Milliseconds(value), index + 2));
+    }
+    Ok((Count::States(value), index))
+}
+
+/// Human readable description of an error, usable in `const` context
+pub const fn describe(error: DslError) -> &'static str {
+    match error {
+        DslError::UnexpectedCharacter => "unexpected character in on/off sequence",
+        DslError::MissingCount => "missing count in on/off sequence",
+        DslError::InvalidCount => "invalid count in on/off sequence",
+        DslError::MissingStatePeriod => "duration in on/off sequence requires a state period",
+        DslError::SequenceTooLong => "on/off sequence has more than 127 states",
+        DslError::Empty => "on/off sequence is empty",
+        DslError::NothingToRepeat => "repeat without states in on/off sequence",
+    }
+}
+
+/// Parse a pattern text at compile time
+///
+/// Expands to a constant `(u128, u16)` tuple of output states and number of
+/// output states. An invalid text fails the build.
+///
+/// # Arguments
+///
+/// * `text` - Pattern in the notation of the [`dsl`](dsl/index.html) module
+/// * `state_period_ms` (optional) - Duration of one output state in
+///   milliseconds, required for durations in `ms`
+///
+/// # Examples
+///
+/// ```rust
+/// use on_off_sequence_output::sequence;
+///
+/// const HEARTBEAT: (u128, u16) = sequence!("#_#_______");
+/// assert_eq!((0b_0000000101, 10), HEARTBEAT);
+///
+/// let (states, len) = sequence!("on 300ms, off 100ms", 100);
+/// assert_eq!((0b0111, 4), (states, len));
+/// ```
+///
+/// ```rust,compile_fail
+/// use on_off_sequence_output::sequence;
+///
+/// let _ = sequence!("#_+");
+/// ```
+#[macro_export]
+macro_rules! sequence {
+    ($text:expr) => {
+        $crate::sequence!($text, 0)
+    };
+    ($text:expr, $state_period_ms:expr) => {{
+        const SEQUENCE: (u128, u16) = match $crate::dsl::parse_timed($text, $state_period_ms) {
+            Ok(sequence) => sequence,
+            Err(error) => panic!("{}", $crate::dsl::describe(error)),
+        };
+        SEQUENCE
+    }};
+}
+
+#[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
+mod tests;
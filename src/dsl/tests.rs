@@ -0,0 +1,121 @@
+/// Tests for the pattern text notation
+use super::{parse, parse_timed, DslError};
+
+#[test]
+fn glyphs_in_reading_order() {
+    assert_eq!(Ok((0b_0001101_u128, 7_u16)), parse("#_##___"));
+    assert_eq!(Ok((0b_0001101_u128, 7_u16)), parse("1011000"));
+    assert_eq!(Ok((0b_0001101_u128, 7_u16)), parse("#-##---"));
+}
+
+#[test]
+fn leading_off_states_count() {
+    assert_eq!(Ok((0b_100_u128, 3_u16)), parse("__#"));
+}
+
+#[test]
+fn blanks_and_commas_are_ignored() {
+    assert_eq!(Ok((0b_0011_0101_u128, 8_u16)), parse(" #_#_, ##__ "));
+}
+
+#[test]
+fn on_off_counts() {
+    assert_eq!(Ok((0b_0_11_u128, 3_u16)), parse("on 2, off 1"));
+    assert_eq!(Ok((0b_111_00_u128, 5_u16)), parse("off 2 on 3"));
+    assert_eq!(Ok((0b_1_0_u128, 2_u16)), parse("off1on1"));
+}
+
+#[test]
+fn mixed_notation() {
+    assert_eq!(Ok((0b_1_000_1_u128, 5_u16)), parse("# off 3 #"));
+}
+
+#[test]
+fn repeat_everything_before() {
+    assert_eq!(Ok((0b_011_011_011_u128, 9_u16)), parse("on 2, off 1 x3"));
+    assert_eq!(Ok((0b_000_01_01_u128, 7_u16)), parse("#_ x2 ___"));
+    assert_eq!(Ok((0b_01_u128, 2_u16)), parse("#_ x1"));
+}
+
+#[test]
+fn durations() {
+    assert_eq!(
+        Ok((0b_0_11_u128, 3_u16)),
+        parse_timed("on 200ms, off 100ms", 100)
+    );
+    // rounded to the nearest number of states, at least one
+    assert_eq!(
+        Ok((0b_0_11_u128, 3_u16)),
+        parse_timed("on 249ms, off 10ms", 100)
+    );
+}
+
+#[test]
+fn durations_need_state_period() {
+    assert_eq!(Err(DslError::MissingStatePeriod), parse("on 200ms"));
+}
+
+#[test]
+fn longest_sequence() {
+    let (sequence, len) = parse("on 127").unwrap();
+    assert_eq!(!0_u128 >> 1, sequence);
+    assert_eq!(127_u16, len);
+}
+
+#[test]
+fn too_long_error() {
+    assert_eq!(Err(DslError::SequenceTooLong), parse("on 128"));
+    assert_eq!(Err(DslError::SequenceTooLong), parse("on 100 #_ x2"));
+    assert_eq!(Err(DslError::SequenceTooLong), parse("on 127 #"));
+}
+
+#[test]
+fn huge_counts_are_too_long() {
+    assert_eq!(Err(DslError::SequenceTooLong), parse("## x4294967295"));
+    assert_eq!(Err(DslError::SequenceTooLong), parse("## x2147483649"));
+    assert_eq!(Err(DslError::SequenceTooLong), parse("# on 4294967295"));
+    assert_eq!(Err(DslError::SequenceTooLong), parse("on 4294967295"));
+    assert_eq!(
+        Err(DslError::SequenceTooLong),
+        parse_timed("# on 4294967295ms", 1)
+    );
+}
+
+#[test]
+fn nothing_to_repeat_error() {
+    assert_eq!(Err(DslError::NothingToRepeat), parse("x4294967295"));
+    assert_eq!(Err(DslError::NothingToRepeat), parse("x3 #_"));
+    assert_eq!(Err(DslError::NothingToRepeat), parse(" , x2"));
+}
+
+#[test]
+fn unexpected_character_error() {
+    assert_eq!(Err(DslError::UnexpectedCharacter), parse("#_+"));
+    assert_eq!(Err(DslError::UnexpectedCharacter), parse("own 2"));
+    assert_eq!(Err(DslError::UnexpectedCharacter), parse("# x2ms"));
+}
+
+#[test]
+fn count_errors() {
+    assert_eq!(Err(DslError::MissingCount), parse("on"));
+    assert_eq!(Err(DslError::MissingCount), parse("# x"));
+    assert_eq!(Err(DslError::InvalidCount), parse("on 0"));
+    assert_eq!(Err(DslError::InvalidCount), parse("# x0"));
+    assert_eq!(Err(DslError::InvalidCount), parse("on 99999999999"));
+}
+
+#[test]
+fn empty_error() {
+    assert_eq!(Err(DslError::Empty), parse(""));
+    assert_eq!(Err(DslError::Empty), parse(" , "));
+}
+
+#[test]
+fn macro_is_const() {
+    const SEQUENCE: (u128, u16) = crate::sequence!("#_##___");
+    assert_eq!((0b_0001101_u128, 7_u16), SEQUENCE);
+    assert_eq!(
+        (0b_011_u128, 3_u16),
+        crate::sequence!("on 200ms off 100ms", 100)
+    );
+}
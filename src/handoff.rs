@@ -0,0 +1,273 @@
+//! Lock-free pattern handoff between a producer and the updating interrupt
+//!
+//! A hard real-time interrupt calling `update()` must never block, not even
+//! on a critical section. A [`PatternHandoff`] is a triple buffer: the
+//! producer stages a new pattern at any time, the interrupt picks up the
+//! latest staged pattern without waiting, and applies it at the requested
+//! boundary, so a running pattern is not cut mid-blink.
+//!
+//! One producer and one consumer are supported; both are obtained by
+//! [`split()`](PatternHandoff::split). Only available on targets with atomic
+//! swap operations (not on `thumbv6m`).
+//!
+//! # Example
+//!
+//! ```rust
+//! use on_off_sequence_output::handoff::*;
+//! use on_off_sequence_output::prelude::*;
+//! # use embedded_hal::digital::v2::OutputPin;
+//! # struct LedPin;
+//! # impl OutputPin for LedPin {
+//! #     type Error = ();
+//! #     fn set_high(&mut self) -> Result<(), ()> { Ok(()) }
+//! #     fn set_low(&mut self) -> Result<(), ()> { Ok(()) }
+//! # }
+//!
+//! let mut handoff = PatternHandoff::new();
+//! let (mut producer, consumer) = handoff.split();
+//!
+//! // owned by the interrupt
+//! let mut led = HandoffOutput::new(OnOffSequenceOutput::new(LedPin, 10), consumer);
+//!
+//! // main loop or any other context
+//! producer.stage(StagedPattern::new(0b0101, 4, Repeat::Forever, Switch::AtRepetitionBoundary));
+//!
+//! // interrupt
+//! led.update().unwrap();
+//! ```
+
+use crate::builder::assert_number_of_states;
+use crate::{OnOffSequenceOutput, OutputUpdate, Repeat};
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU8, Ordering};
+use embedded_hal::digital::v2::OutputPin;
+
+/// Flag of the shared slot index: the slot holds a not yet taken pattern
+const NEW_PATTERN: u8 = 0b100;
+
+/// Mask of the shared slot index
+const SLOT_INDEX: u8 = 0b011;
+
+/// When a staged pattern replaces the running one
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Switch {
+    /// At the next update, the current state may be cut short
+    Immediately,
+    /// After the current state lasted its full duration
+    AtStateBoundary,
+    /// After the current run of the output states is completed
+    AtRepetitionBoundary,
+}
+
+/// A pattern handed over to the updating context
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StagedPattern {
+    output_states: u128,
+    number_of_output_states: u16,
+    repeat: Repeat,
+    switch: Switch,
+}
+
+impl StagedPattern {
+    /// Creates a pattern to be staged
+    ///
+    /// # Arguments
+    ///
+    /// * `output_states`, `number_of_output_states`, `repeat` - as for
+    ///   `OnOffSequenceOutput::set()`
+    /// * `switch` - When the pattern replaces the running one
+    ///
+    /// # Notes
+    ///
+    /// * Panics on the same number of output states as `set()` does, but in
+    ///   the producing context rather than in the interrupt.
+    pub fn new(
+        output_states: u128,
+        number_of_output_states: u16,
+        repeat: Repeat,
+        switch: Switch,
+    ) -> Self {
+        assert_number_of_states(number_of_output_states);
+        Self {
+            output_states,
+            number_of_output_states,
+            repeat,
+            switch,
+        }
+    }
+
+    const fn off() -> Self {
+        Self {
+            output_states: 0,
+            number_of_output_states: 1,
+            repeat: Repeat::Never,
+            switch: Switch::Immediately,
+        }
+    }
+}
+
+/// Triple buffer for staged patterns
+pub struct PatternHandoff {
+    slots: [UnsafeCell<StagedPattern>; 3],
+    /// Index of the slot shared between producer and consumer, or-ed with
+    /// `NEW_PATTERN` if the producer published into it
+    shared: AtomicU8,
+}
+
+// Each slot is accessed either by the producer, by the consumer or by none of
+// them; ownership of a slot changes by swapping indices atomically.
+unsafe impl Sync for PatternHandoff {}
+
+impl PatternHandoff {
+    /// Creates an empty handoff
+    pub const fn new() -> Self {
+        Self {
+            slots: [
+                UnsafeCell::new(StagedPattern::off()),
+                UnsafeCell::new(StagedPattern::off()),
+                UnsafeCell::new(StagedPattern::off()),
+            ],
+            shared: AtomicU8::new(1),
+        }
+    }
+
+    /// Splits into the producer and the consumer handle
+    pub fn split(&mut self) -> (PatternProducer<'_>, PatternConsumer<'_>) {
+        self.shared.store(1, Ordering::Relaxed);
+        let handoff: &Self = self;
+        (
+            PatternProducer { handoff, slot: 0 },
+            PatternConsumer { handoff, slot: 2 },
+        )
+    }
+}
+
+impl Default for PatternHandoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Producer handle of a [`PatternHandoff`]
+pub struct PatternProducer<'a> {
+    handoff: &'a PatternHandoff,
+    /// Slot owned by the producer
+    slot: u8,
+}
+
+impl PatternProducer<'_> {
+    /// Stages a pattern, it replaces a staged pattern not yet taken
+    ///
+    /// Never blocks.
+    pub fn stage(&mut self, pattern: StagedPattern) {
+        // the slot is owned by the producer, see `PatternHandoff`
+        unsafe { *self.handoff.slots[self.slot as usize].get() = pattern };
+        let previous = self
+            .handoff
+            .shared
+            .swap(self.slot | NEW_PATTERN, Ordering::AcqRel);
+        self.slot = previous & SLOT_INDEX;
+    }
+}
+
+/// Consumer handle of a [`PatternHandoff`]
+pub struct PatternConsumer<'a> {
+    handoff: &'a PatternHandoff,
+    /// Slot owned by the consumer
+    slot: u8,
+}
+
+impl PatternConsumer<'_> {
+    /// Takes the latest staged pattern
+    ///
+    /// Never blocks.
+    ///
+    /// # Returns
+    ///
+    /// * Some(pattern) - if a pattern was staged since the last call
+    /// * None - otherwise
+    pub fn take(&mut self) -> Option<StagedPattern> {
+        if self.handoff.shared.load(Ordering::Relaxed) & NEW_PATTERN == 0 {
+            return None;
+        }
+        let shared = self.handoff.shared.swap(self.slot, Ordering::AcqRel);
+        self.slot = shared & SLOT_INDEX;
+        // the slot is owned by the consumer, see `PatternHandoff`
+        Some(unsafe { *self.handoff.slots[self.slot as usize].get() })
+    }
+}
+
+/// Output that picks up staged patterns on `update()`
+pub struct HandoffOutput<'a, T: OutputPin> {
+    output: OnOffSequenceOutput<T>,
+    consumer: PatternConsumer<'a>,
+    /// Taken pattern waiting for its boundary
+    pending: Option<StagedPattern>,
+}
+
+impl<'a, T: OutputPin> HandoffOutput<'a, T> {
+    /// Combines an output and the consumer of a handoff
+    pub fn new(output: OnOffSequenceOutput<T>, consumer: PatternConsumer<'a>) -> Self {
+        Self {
+            output,
+            consumer,
+            pending: None,
+        }
+    }
+
+    /// Splits back into the output and the consumer of the handoff
+    pub fn release(self) -> (OnOffSequenceOutput<T>, PatternConsumer<'a>) {
+        (self.output, self.consumer)
+    }
+
+    /// Check if a taken pattern waits for its boundary
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Takes a staged pattern and applies it if its boundary is reached
+    fn apply_staged(&mut self) {
+        if let Some(pattern) = self.consumer.take() {
+            self.pending = Some(pattern);
+        }
+        if let Some(pattern) = self.pending {
+            let at_boundary = match pattern.switch {
+                Switch::Immediately => true,
+                Switch::AtStateBoundary => self.output.at_state_boundary(),
+                Switch::AtRepetitionBoundary => self.output.at_repetition_boundary(),
+            };
+            if at_boundary {
+                self.output.set(
+                    pattern.output_states,
+                    pattern.number_of_output_states,
+                    pattern.repeat,
+                );
+                self.pending = None;
+            }
+        }
+    }
+}
+
+impl<T: OutputPin> OutputUpdate for HandoffOutput<'_, T> {
+    type Error = T::Error;
+
+    /// Applies a staged pattern at its boundary and updates the output
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        self.apply_staged();
+        self.output.update()
+    }
+
+    /// Applies a staged pattern at its boundary and updates the output by
+    /// `OnOffSequenceOutput::update_by()`
+    ///
+    /// # Notes
+    ///
+    /// * The boundary is checked before the elapsed update periods only
+    fn update_by(&mut self, ticks: u16) -> Result<bool, Self::Error> {
+        self.apply_staged();
+        self.output.update_by(ticks)
+    }
+}
+
+#[cfg(test)]
+mod tests;
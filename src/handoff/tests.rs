@@ -0,0 +1,186 @@
+/// Tests for the lock-free pattern handoff
+use super::*;
+use crate::tests::mocks::*;
+
+fn pattern(output_states: u128, number_of_output_states: u16, switch: Switch) -> StagedPattern {
+    StagedPattern::new(
+        output_states,
+        number_of_output_states,
+        Repeat::Never,
+        switch,
+    )
+}
+
+mod pattern_handoff {
+    use super::*;
+
+    #[test]
+    fn nothing_staged() {
+        let mut handoff = PatternHandoff::new();
+        let (_, mut consumer) = handoff.split();
+        assert_eq!(None, consumer.take());
+    }
+
+    #[test]
+    fn take_once() {
+        let mut handoff = PatternHandoff::new();
+        let (mut producer, mut consumer) = handoff.split();
+        producer.stage(pattern(0b1, 1, Switch::Immediately));
+        assert_eq!(Some(pattern(0b1, 1, Switch::Immediately)), consumer.take());
+        assert_eq!(None, consumer.take());
+    }
+
+    #[test]
+    fn latest_wins() {
+        let mut handoff = PatternHandoff::new();
+        let (mut producer, mut consumer) = handoff.split();
+        for n in 1..=5 {
+            producer.stage(pattern(0b1, n, Switch::Immediately));
+        }
+        assert_eq!(Some(pattern(0b1, 5, Switch::Immediately)), consumer.take());
+        assert_eq!(None, consumer.take());
+    }
+
+    #[test]
+    fn alternating() {
+        let mut handoff = PatternHandoff::new();
+        let (mut producer, mut consumer) = handoff.split();
+        for n in 1..=10 {
+            producer.stage(pattern(0b1, n, Switch::Immediately));
+            assert_eq!(Some(pattern(0b1, n, Switch::Immediately)), consumer.take());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_zero_sequence_length() {
+        pattern(0b1, 0, Switch::Immediately);
+    }
+}
+
+mod handoff_output {
+    use super::*;
+
+    #[test]
+    fn default_pattern_without_staging() -> Result<(), MockedOutputPinError> {
+        let mut handoff = PatternHandoff::new();
+        let (_, consumer) = handoff.split();
+        let pin_mock = MockedOutputPin::expected(2, 0b10_u128);
+        let mut ledout = HandoffOutput::new(OnOffSequenceOutput::new(pin_mock, 1), consumer);
+        ledout.update()?;
+        ledout.update()?;
+        Ok(())
+    }
+
+    #[test]
+    fn switch_immediately() -> Result<(), MockedOutputPinError> {
+        let mut handoff = PatternHandoff::new();
+        let (mut producer, consumer) = handoff.split();
+        // state 0 of the default pattern is cut after one of two periods
+        let pin_mock = MockedOutputPin::expected(2, 0b10_u128);
+        let mut ledout = HandoffOutput::new(OnOffSequenceOutput::new(pin_mock, 2), consumer);
+        for _ in 0..3 {
+            ledout.update()?;
+        }
+        producer.stage(pattern(0b1, 1, Switch::Immediately));
+        assert!(!ledout.update()?);
+        assert!(ledout.update()?);
+        Ok(())
+    }
+
+    #[test]
+    fn switch_at_state_boundary() -> Result<(), MockedOutputPinError> {
+        let mut handoff = PatternHandoff::new();
+        let (mut producer, consumer) = handoff.split();
+        let pin_mock = MockedOutputPin::expected(4, 0b1110_u128);
+        let mut ledout = HandoffOutput::new(OnOffSequenceOutput::new(pin_mock, 2), consumer);
+        for _ in 0..3 {
+            ledout.update()?;
+        }
+        producer.stage(pattern(0b11, 2, Switch::AtStateBoundary));
+        // second state of the default pattern lasts its full duration
+        assert!(!ledout.update()?);
+        assert!(ledout.is_pending());
+        assert!(!ledout.update()?);
+        assert!(!ledout.is_pending());
+        for _ in 0..2 {
+            assert!(!ledout.update()?);
+        }
+        assert!(ledout.update()?);
+        Ok(())
+    }
+
+    #[test]
+    fn switch_at_repetition_boundary() -> Result<(), MockedOutputPinError> {
+        let mut handoff = PatternHandoff::new();
+        let (mut producer, consumer) = handoff.split();
+        let pin_mock = MockedOutputPin::expected(5, 0b11_110_u128);
+        let mut ledout = HandoffOutput::new(OnOffSequenceOutput::new(pin_mock, 1), consumer);
+        ledout.output.set(0b110, 3, Repeat::Forever);
+        ledout.update()?;
+        producer.stage(pattern(0b11, 2, Switch::AtRepetitionBoundary));
+        // the run of 0b110 is completed first
+        ledout.update()?;
+        ledout.update()?;
+        assert!(ledout.is_pending());
+        assert!(!ledout.update()?);
+        assert!(ledout.update()?);
+        Ok(())
+    }
+
+    #[test]
+    fn switch_after_completion() -> Result<(), MockedOutputPinError> {
+        let mut handoff = PatternHandoff::new();
+        let (mut producer, consumer) = handoff.split();
+        let pin_mock = MockedOutputPin::expected(2, 0b01_u128);
+        let mut ledout = HandoffOutput::new(OnOffSequenceOutput::new(pin_mock, 3), consumer);
+        ledout.output.set(0b1, 1, Repeat::Never);
+        for _ in 0..4 {
+            ledout.update()?;
+        }
+        producer.stage(pattern(0b0, 1, Switch::AtRepetitionBoundary));
+        ledout.update()?;
+        assert!(!ledout.is_pending());
+        assert!(!ledout.update()?);
+        assert!(ledout.update()?);
+        Ok(())
+    }
+
+    #[test]
+    fn update_by_zero_ticks() -> Result<(), MockedOutputPinError> {
+        let mut handoff = PatternHandoff::new();
+        let (_, consumer) = handoff.split();
+        let pin_mock = MockedOutputPin::expected(0, 0b0_u128);
+        let mut ledout = HandoffOutput::new(OnOffSequenceOutput::new(pin_mock, 1), consumer);
+        for _ in 0..10 {
+            assert!(!ledout.update_by(0)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn update_by_applies_catch_up() -> Result<(), MockedOutputPinError> {
+        let mut handoff = PatternHandoff::new();
+        let (mut producer, consumer) = handoff.split();
+        let pin_mock = MockedOutputPin::expected(2, 0b01_u128);
+        let mut ledout = HandoffOutput::new(OnOffSequenceOutput::new(pin_mock, 1), consumer);
+        ledout.output.set_catch_up(crate::CatchUp::SkipToCurrent);
+        producer.stage(pattern(0b0110, 4, Switch::Immediately));
+        // states 0..=2 elapsed, only state 2 is driven
+        assert!(!ledout.update_by(3)?);
+        assert!(!ledout.is_pending());
+        assert!(ledout.update_by(1)?);
+        Ok(())
+    }
+
+    #[test]
+    fn release_returns_parts() {
+        let mut handoff = PatternHandoff::new();
+        let (mut producer, consumer) = handoff.split();
+        let pin_mock = MockedOutputPin::expected(0, 0b0_u128);
+        let ledout = HandoffOutput::new(OnOffSequenceOutput::new(pin_mock, 1), consumer);
+        let (_, mut consumer) = ledout.release();
+        producer.stage(pattern(0b1, 1, Switch::Immediately));
+        assert!(consumer.take().is_some());
+    }
+}
@@ -0,0 +1,422 @@
+//! Infrared remote control frames
+//!
+//! IR remotes key a carrier, typically 36 to 40 kHz, on and off: a mark is
+//! the carrier on, a space the carrier off. The protocol encoders turn an
+//! address and a command into an [`IrFrame`], a sequence of marks and
+//! spaces with individual durations:
+//!
+//! * [`nec()`], [`nec_extended()`] and [`nec_repeat()`] - NEC, 38 kHz
+//! * [`rc5()`] - Philips RC5 and RC5X, 36 kHz
+//! * [`sirc12()`], [`sirc15()`] and [`sirc20()`] - Sony SIRC, 40 kHz
+//!
+//! Every frame ends with a space up to the frame period of its protocol, so
+//! frames repeat with the right timing.
+//!
+//! An [`IrOutput`] plays frames through the `OutputUpdate` loop, updated at
+//! a fixed tick. Marks and spaces switch a [`Carrier`]: a PWM channel
+//! generating the carrier ([`PwmCarrier`]), or a pin toggled on every
+//! update during a mark ([`ToggledCarrier`]), which needs updates at twice
+//! the carrier frequency.
+//!
+//! # Example
+//!
+//! ```rust
+//! use on_off_sequence_output::ir;
+//!
+//! let frame = ir::nec(0x04, 0x08);
+//! // leader mark and space, 32 bits, stop mark, final space
+//! assert_eq!(68, frame.as_slice().len());
+//! assert_eq!(&[9000, 4500, 562, 562], &frame.as_slice()[..4]);
+//! assert_eq!(108_000, frame.duration());
+//! ```
+//!
+//! ```rust,ignore
+//! use on_off_sequence_output::ir::{self, IrOutput, PwmCarrier};
+//! use on_off_sequence_output::prelude::*;
+//!
+//! // updated every 50 us by a timer interrupt
+//! let mut remote = IrOutput::new(PwmCarrier(pwm_38khz), 50);
+//! remote.send(ir::nec(0x04, 0x08), Repeat::Never);
+//! loop {
+//!    if remote.update().unwrap() { break; };
+//!    wait(50.us());
+//! }
+//! ```
+
+use crate::progress::Progress;
+use crate::{OutputUpdate, Repeat};
+use core::convert::Infallible;
+use embedded_hal::digital::v2::OutputPin;
+use embedded_hal::PwmPin;
+
+/// Maximum number of marks and spaces of a frame
+pub const MAX_NUMBER_OF_ELEMENTS: usize = 68;
+
+/// Duration of the NEC unit in microseconds, rounded down from 562.5
+const NEC_UNIT: u32 = 562;
+/// Space of a NEC one in microseconds, rounded down from 1687.5
+const NEC_ONE_SPACE: u32 = 1687;
+/// Leader mark of NEC frames and repeat codes in microseconds
+const NEC_LEADER_MARK: u32 = 9000;
+/// Leader space of NEC frames in microseconds
+const NEC_LEADER_SPACE: u32 = 4500;
+/// Leader space of NEC repeat codes in microseconds
+const NEC_REPEAT_SPACE: u32 = 2250;
+/// Start-to-start period of NEC frames in microseconds
+const NEC_PERIOD: u32 = 108_000;
+
+/// Duration of a RC5 half-bit in microseconds
+const RC5_HALF_BIT: u32 = 889;
+/// Start-to-start period of RC5 frames, 64 bit times, in microseconds
+const RC5_PERIOD: u32 = 64 * 2 * RC5_HALF_BIT;
+
+/// Duration of the SIRC unit in microseconds
+const SIRC_UNIT: u32 = 600;
+/// Start-to-start period of SIRC frames in microseconds
+const SIRC_PERIOD: u32 = 45_000;
+
+/// Frame encoding errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IrError {
+    /// The address has more bits than the protocol allows
+    AddressOutOfRange,
+    /// The command has more bits than the protocol allows
+    CommandOutOfRange,
+}
+
+/// Marks and spaces of an IR frame with their durations
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IrFrame {
+    /// Durations in microseconds, marks at even indices
+    durations: [u32; MAX_NUMBER_OF_ELEMENTS],
+    len: usize,
+}
+
+impl IrFrame {
+    const fn empty() -> Self {
+        Self {
+            durations: [0; MAX_NUMBER_OF_ELEMENTS],
+            len: 0,
+        }
+    }
+
+    /// Appends a mark or space, joins it with a preceding one of the same
+    /// kind; leading spaces are dropped
+    fn push(&mut self, mark: bool, duration: u32) {
+        let last_is_mark = self.len % 2 == 1;
+        if self.len > 0 && last_is_mark == mark {
+            self.durations[self.len - 1] += duration;
+        } else if self.len > 0 || mark {
+            self.durations[self.len] = duration;
+            self.len += 1;
+        }
+    }
+
+    /// Appends a space up to the given start-to-start period
+    fn pad_to(&mut self, period: u32) {
+        let duration = self.duration();
+        if duration < period {
+            self.push(false, period - duration);
+        }
+    }
+
+    /// Durations of the marks and spaces in microseconds, alternating and
+    /// starting with a mark
+    pub fn as_slice(&self) -> &[u32] {
+        &self.durations[..self.len]
+    }
+
+    /// Total duration of the frame in microseconds, the frame period of the
+    /// protocol
+    pub fn duration(&self) -> u32 {
+        self.as_slice().iter().sum()
+    }
+}
+
+/// NEC frame with an 8-bit address
+///
+/// Address and command are followed by their inverse, LSB first.
+pub fn nec(address: u8, command: u8) -> IrFrame {
+    nec_extended(u16::from_le_bytes([address, !address]), command)
+}
+
+/// NEC frame with a 16-bit address, LSB first
+pub fn nec_extended(address: u16, command: u8) -> IrFrame {
+    let mut frame = IrFrame::empty();
+    frame.push(true, NEC_LEADER_MARK);
+    frame.push(false, NEC_LEADER_SPACE);
+    let bits = u32::from(address) | u32::from(command) << 16 | u32::from(!command) << 24;
+    for bit in 0..32 {
+        frame.push(true, NEC_UNIT);
+        if bits & (1 << bit) != 0 {
+            frame.push(false, NEC_ONE_SPACE);
+        } else {
+            frame.push(false, NEC_UNIT);
+        }
+    }
+    frame.push(true, NEC_UNIT);
+    frame.pad_to(NEC_PERIOD);
+    frame
+}
+
+/// NEC repeat code, sent every frame period as long as a key is held
+pub fn nec_repeat() -> IrFrame {
+    let mut frame = IrFrame::empty();
+    frame.push(true, NEC_LEADER_MARK);
+    frame.push(false, NEC_REPEAT_SPACE);
+    frame.push(true, NEC_UNIT);
+    frame.pad_to(NEC_PERIOD);
+    frame
+}
+
+/// RC5 frame, RC5X for commands from 64 up to 127
+///
+/// # Arguments
+///
+/// * `address` - 5-bit address
+/// * `command` - 6-bit command, or 7-bit for RC5X
+/// * `toggle` - To be inverted on every key press
+pub fn rc5(address: u8, command: u8, toggle: bool) -> Result<IrFrame, IrError> {
+    if address >= 1 << 5 {
+        return Err(IrError::AddressOutOfRange);
+    }
+    if command >= 1 << 7 {
+        return Err(IrError::CommandOutOfRange);
+    }
+    // start bits, toggle, address and command, MSB first; the second start
+    // bit is the inverted 7th command bit
+    let bits = 1 << 13
+        | u16::from(command < 1 << 6) << 12
+        | u16::from(toggle) << 11
+        | u16::from(address) << 6
+        | u16::from(command & 0b11_1111);
+    let mut frame = IrFrame::empty();
+    for bit in (0..14).rev() {
+        // Manchester: a one is a space followed by a mark
+        let one = bits & (1 << bit) != 0;
+        frame.push(!one, RC5_HALF_BIT);
+        frame.push(one, RC5_HALF_BIT);
+    }
+    frame.pad_to(RC5_PERIOD);
+    Ok(frame)
+}
+
+/// SIRC frame with 7-bit command and 5-bit address
+pub fn sirc12(address: u8, command: u8) -> Result<IrFrame, IrError> {
+    if address >= 1 << 5 {
+        return Err(IrError::AddressOutOfRange);
+    }
+    sirc(u32::from(address), 5, command)
+}
+
+/// SIRC frame with 7-bit command and 8-bit address
+pub fn sirc15(address: u8, command: u8) -> Result<IrFrame, IrError> {
+    sirc(u32::from(address), 8, command)
+}
+
+/// SIRC frame with 7-bit command, 5-bit address and 8-bit extended data
+pub fn sirc20(address: u8, command: u8, extended: u8) -> Result<IrFrame, IrError> {
+    if address >= 1 << 5 {
+        return Err(IrError::AddressOutOfRange);
+    }
+    sirc(u32::from(address) | u32::from(extended) << 5, 13, command)
+}
+
+/// SIRC frame, command and address LSB first
+fn sirc(address: u32, address_bits: u32, command: u8) -> Result<IrFrame, IrError> {
+    if command >= 1 << 7 {
+        return Err(IrError::CommandOutOfRange);
+    }
+    let bits = u32::from(command) | address << 7;
+    let mut frame = IrFrame::empty();
+    frame.push(true, 4 * SIRC_UNIT);
+    for bit in 0..7 + address_bits {
+        frame.push(false, SIRC_UNIT);
+        if bits & (1 << bit) != 0 {
+            frame.push(true, 2 * SIRC_UNIT);
+        } else {
+            frame.push(true, SIRC_UNIT);
+        }
+    }
+    frame.pad_to(SIRC_PERIOD);
+    Ok(frame)
+}
+
+/// Switches the carrier of an IR LED
+pub trait Carrier {
+    /// Error type of the switching
+    type Error;
+
+    /// Switches the carrier on, called when a mark starts
+    fn mark(&mut self) -> Result<(), Self::Error>;
+
+    /// Switches the carrier off, called when a space starts
+    fn space(&mut self) -> Result<(), Self::Error>;
+
+    /// Called on every further update during a mark, e.g. to toggle the
+    /// carrier; does nothing by default
+    fn refresh(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Carrier generated by a PWM channel, e.g. at 50 % duty cycle
+pub struct PwmCarrier<P: PwmPin>(pub P);
+
+impl<P: PwmPin> Carrier for PwmCarrier<P> {
+    type Error = Infallible;
+
+    fn mark(&mut self) -> Result<(), Self::Error> {
+        self.0.enable();
+        Ok(())
+    }
+
+    fn space(&mut self) -> Result<(), Self::Error> {
+        self.0.disable();
+        Ok(())
+    }
+}
+
+/// Carrier generated by toggling a pin on every update during a mark
+///
+/// The update frequency needs to be twice the carrier frequency.
+pub struct ToggledCarrier<P: OutputPin> {
+    /// The pin of the IR LED, high is lit
+    pub pin: P,
+    high: bool,
+}
+
+impl<P: OutputPin> ToggledCarrier<P> {
+    /// Carrier on a pin, the pin is low
+    pub fn new(pin: P) -> Self {
+        Self { pin, high: false }
+    }
+}
+
+impl<P: OutputPin> Carrier for ToggledCarrier<P> {
+    type Error = P::Error;
+
+    fn mark(&mut self) -> Result<(), Self::Error> {
+        self.high = true;
+        self.pin.set_high()
+    }
+
+    fn space(&mut self) -> Result<(), Self::Error> {
+        self.high = false;
+        self.pin.set_low()
+    }
+
+    fn refresh(&mut self) -> Result<(), Self::Error> {
+        if self.high {
+            self.space()
+        } else {
+            self.mark()
+        }
+    }
+}
+
+/// Output of IR frames on a carrier
+pub struct IrOutput<C: Carrier> {
+    /// Switches the carrier
+    pub carrier: C,
+
+    /// Period of the updates in microseconds
+    tick_us: u32,
+
+    /// The frame sent
+    frame: IrFrame,
+
+    /// Position and repetitions of the frame, one state per mark or space
+    progress: Progress,
+
+    /// Whether the current mark or space was started
+    started: bool,
+
+    /// Microseconds left of the current mark or space; negative if the
+    /// last one lasted longer, which shortens the next one
+    remaining: i64,
+}
+
+impl<C: Carrier> IrOutput<C> {
+    /// Initializes a new IR output
+    ///
+    /// # Arguments
+    ///
+    /// * `carrier` - Switches the carrier of the IR LED, off
+    /// * `tick_us` - Period of the updates in microseconds
+    ///
+    /// # Notes
+    ///
+    /// * Default is sending nothing
+    ///
+    /// # Panics
+    ///
+    /// If the tick is zero
+    pub fn new(carrier: C, tick_us: u32) -> Self {
+        if tick_us == 0 {
+            panic!("Zero tick does not make sense");
+        };
+        let mut progress = Progress::new(1, 1, Repeat::Never);
+        progress.run_output = false;
+        Self {
+            carrier,
+            tick_us,
+            frame: IrFrame::empty(),
+            progress,
+            started: false,
+            remaining: 0,
+        }
+    }
+
+    /// Send a frame, starting with the next update
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame to send
+    /// * `repeat` - How often is the frame repeated, e.g. while a key is
+    ///   held
+    pub fn send(&mut self, frame: IrFrame, repeat: Repeat) {
+        self.frame = frame;
+        self.progress.restart(frame.len as u16, repeat);
+        self.progress.run_output = frame.len > 0;
+        self.started = false;
+        self.remaining = 0;
+    }
+}
+
+impl<C: Carrier> OutputUpdate for IrOutput<C> {
+    type Error = C::Error;
+
+    /// Updates the output, potentially switches the carrier
+    ///
+    /// # Returns
+    ///
+    /// true if the frame and its repetitions are sent
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        let index = match self.progress.current() {
+            Some(index) => index as usize,
+            None => return Ok(true),
+        };
+        let mark = index % 2 == 0;
+        if !self.started {
+            self.started = true;
+            self.remaining += i64::from(self.frame.durations[index]);
+            if mark {
+                self.carrier.mark()?;
+            } else {
+                self.carrier.space()?;
+            }
+        } else if mark {
+            self.carrier.refresh()?;
+        }
+        self.remaining -= i64::from(self.tick_us);
+        if self.remaining <= 0 {
+            self.started = false;
+            self.progress.advance();
+        }
+        Ok(self.progress.is_completed())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,165 @@
+/// Tests for the IR frames and output
+use super::*;
+use crate::tests::mocks::*;
+
+mod encoders {
+    use super::*;
+
+    #[test]
+    fn nec() {
+        let frame = super::nec(0x00, 0x01);
+        let elements = frame.as_slice();
+        assert_eq!(MAX_NUMBER_OF_ELEMENTS, elements.len());
+        assert_eq!(&[9000, 4500, 562, 562], &elements[..4]);
+        // first bit of the inverted address
+        assert_eq!(562, elements[18]);
+        assert_eq!(1687, elements[19]);
+        // first bit of the command
+        assert_eq!(1687, elements[35]);
+        // stop mark
+        assert_eq!(562, elements[66]);
+        assert_eq!(108_000, frame.duration());
+    }
+
+    #[test]
+    fn nec_extended_address() {
+        let frame = nec_extended(0x0102, 0x00);
+        let elements = frame.as_slice();
+        assert_eq!(1687, elements[5]);
+        assert_eq!(1687, elements[19]);
+        assert_eq!(562, elements[21]);
+    }
+
+    #[test]
+    fn nec_repeat() {
+        let frame = super::nec_repeat();
+        assert_eq!(&[9000, 2250, 562, 96_188], frame.as_slice());
+    }
+
+    #[test]
+    fn rc5() {
+        // start bits, toggle 0, address 00101, command 100011
+        let frame = super::rc5(5, 35, false).unwrap();
+        let half_bits = [
+            1, 1, 2, 1, 1, 1, 1, 2, 2, 2, 1, 1, 2, 1, 1, 1, 1, 2, 1, 1, 1,
+        ];
+        let elements = frame.as_slice();
+        assert_eq!(half_bits.len() + 1, elements.len());
+        for (duration, half_bits) in elements.iter().zip(half_bits.iter()) {
+            assert_eq!(half_bits * 889, *duration);
+        }
+        assert_eq!(64 * 1778, frame.duration());
+    }
+
+    #[test]
+    fn rc5x_and_toggle() {
+        // the second start bit is zero for RC5X, followed by toggle 1 and
+        // address 0: mark of S1 and S2, space of S2 and toggle, mark of
+        // toggle and the first address bit
+        let frame = super::rc5(0, 64, true).unwrap();
+        assert_eq!(&[1778, 1778, 1778, 889, 889], &frame.as_slice()[..5]);
+    }
+
+    #[test]
+    fn rc5_errors() {
+        assert_eq!(Err(IrError::AddressOutOfRange), super::rc5(32, 0, false));
+        assert_eq!(Err(IrError::CommandOutOfRange), super::rc5(0, 128, false));
+    }
+
+    #[test]
+    fn sirc12() {
+        // command 0010101 and address 00001, LSB first
+        let frame = super::sirc12(1, 21).unwrap();
+        let elements = frame.as_slice();
+        assert_eq!(1 + 2 * 12 + 1, elements.len());
+        assert_eq!(&[2400, 600, 1200, 600, 600, 600, 1200], &elements[..7]);
+        assert_eq!(1200, elements[16]);
+        assert_eq!(600, elements[18]);
+        assert_eq!(45_000, frame.duration());
+    }
+
+    #[test]
+    fn sirc15_and_sirc20() {
+        assert_eq!(1 + 2 * 15 + 1, sirc15(0xff, 0).unwrap().as_slice().len());
+        let frame = sirc20(0, 0, 0x80).unwrap();
+        assert_eq!(1 + 2 * 20 + 1, frame.as_slice().len());
+        assert_eq!(1200, frame.as_slice()[40]);
+    }
+
+    #[test]
+    fn sirc_errors() {
+        assert_eq!(Err(IrError::AddressOutOfRange), super::sirc12(32, 0));
+        assert_eq!(Err(IrError::CommandOutOfRange), sirc15(0, 128));
+        assert_eq!(Err(IrError::AddressOutOfRange), sirc20(32, 0, 0));
+    }
+}
+
+mod ir_output {
+    use super::*;
+
+    /// Counts the carrier switching
+    #[derive(Default)]
+    struct CountingCarrier {
+        marks: u16,
+        spaces: u16,
+        refreshes: u16,
+    }
+
+    impl Carrier for CountingCarrier {
+        type Error = MockedOutputPinError;
+
+        fn mark(&mut self) -> Result<(), Self::Error> {
+            self.marks += 1;
+            Ok(())
+        }
+
+        fn space(&mut self) -> Result<(), Self::Error> {
+            self.spaces += 1;
+            Ok(())
+        }
+
+        fn refresh(&mut self) -> Result<(), Self::Error> {
+            self.refreshes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn nothing_to_send() -> Result<(), MockedOutputPinError> {
+        let mut remote = IrOutput::new(CountingCarrier::default(), 1000);
+        assert!(remote.update()?);
+        assert_eq!(0, remote.carrier.marks + remote.carrier.spaces);
+        Ok(())
+    }
+
+    #[test]
+    fn frame_period_in_ticks() -> Result<(), MockedOutputPinError> {
+        let mut remote = IrOutput::new(CountingCarrier::default(), 1000);
+        remote.send(nec_repeat(), Repeat::Times(1));
+        for _ in 1..216 {
+            assert!(!remote.update()?);
+        }
+        assert!(remote.update()?);
+        assert_eq!(4, remote.carrier.marks);
+        assert_eq!(4, remote.carrier.spaces);
+        // the leader mark lasts 9 updates
+        assert_eq!(16, remote.carrier.refreshes);
+        Ok(())
+    }
+
+    #[test]
+    fn toggled_carrier() -> Result<(), MockedOutputPinError> {
+        let pin_mock = MockedOutputPin::expected(4, 0b0101_u128);
+        let mut carrier = ToggledCarrier::new(pin_mock);
+        carrier.mark()?;
+        carrier.refresh()?;
+        carrier.refresh()?;
+        carrier.space()
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_tick() {
+        let _ = IrOutput::new(CountingCarrier::default(), 0);
+    }
+}
@@ -45,27 +45,119 @@
 //!    led.update().unwrap();
 //!    wait(1.ms());
 //! }
-
 //! ```
 
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
+#[macro_use]
+mod trace;
+
 pub mod prelude;
 
+mod progress;
+
+mod builder;
+
 pub mod morse;
 
+pub mod dsl;
+
+pub mod patterns;
+
+pub mod pattern;
+
+pub mod blink_code;
+
+pub mod line_code;
+
+#[cfg(feature = "critical-section")]
+pub mod shared;
+
+#[cfg(target_has_atomic = "8")]
+pub mod handoff;
+
+pub mod timed;
+
+pub mod verify;
+
+pub mod bicolor;
+
+pub mod sequencer;
+
+pub mod matrix;
+
+pub mod segments;
+
+pub mod batch;
+
+pub mod ir;
+
+pub mod command;
+
+#[cfg(feature = "std")]
+pub mod runner;
+
+use builder::assert_number_of_states;
+use dsl::DslError;
 use embedded_hal::digital::v2::OutputPin;
 use morse::{str_to_morse, MorseError};
+use pattern::Pattern;
+use patterns::PatternError;
+use progress::Progress;
 // use bitset_core::BitSet;
 
 /// How often shall the output repeated
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum Repeat {
     Never,
     Times(u16),
     Forever,
 }
 
+/// In which order the bits of the output states are played
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BitOrder {
+    /// Lsb is played first; a literal `0b1110` plays off-on-on-on
+    LsbFirst,
+    /// The highest considered bit is played first, i.e. the bits are played
+    /// left to right as written; a literal `0b1110` plays on-on-on-off
+    MsbFirst,
+}
+
+impl BitOrder {
+    /// Convert output states of this bit order into lsb first order
+    ///
+    /// # Arguments
+    ///
+    /// * `output_states` - bits of a unsigned number in this bit order
+    /// * `number_of_output_states` - how many bits are considered, counted
+    ///   from lsb
+    ///
+    /// # Returns
+    ///
+    /// The considered output states in lsb first order, all other bits cleared
+    /// for `MsbFirst`.
+    pub const fn to_lsb_first(self, output_states: u128, number_of_output_states: u16) -> u128 {
+        match self {
+            BitOrder::LsbFirst => output_states,
+            BitOrder::MsbFirst => {
+                if number_of_output_states == 0 {
+                    0
+                } else if number_of_output_states >= 128 {
+                    output_states.reverse_bits()
+                } else {
+                    output_states.reverse_bits() >> (128 - number_of_output_states)
+                }
+            }
+        }
+    }
+}
+
 /// OutputUpdate Trait which provides an `update()` method
 pub trait OutputUpdate {
     type Error;
@@ -84,6 +176,68 @@ pub trait OutputUpdate {
     /// * Needs to be called periodically.
     /// * Side Effect: calls the aggregated GPIO pin to switch on/off
     fn update(&mut self) -> Result<bool, Self::Error>;
+
+    /// Updates the output logic for a number of elapsed update periods
+    ///
+    /// Use it instead of `update()` if calls might be delayed, e.g. by
+    /// interrupt storms or flash writes, so states do not stretch.
+    ///
+    /// # Arguments
+    ///
+    /// * `ticks` - Number of update periods elapsed since the last call
+    ///
+    /// # Returns
+    ///
+    /// Same as `update()`
+    ///
+    /// # Notes
+    ///
+    /// * The default implementation calls `update()` `ticks` times; zero
+    ///   ticks do nothing and return false.
+    fn update_by(&mut self, ticks: u16) -> Result<bool, Self::Error> {
+        let mut done = false;
+        for _ in 0..ticks {
+            done = self.update()?;
+        }
+        Ok(done)
+    }
+}
+
+/// How `update_by()` catches up with missed state changes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CatchUp {
+    /// Drive every missed state in order, i.e. replay all edges in a burst
+    ReplayEdges,
+    /// Advance over missed states silently and drive the current state only
+    SkipToCurrent,
+}
+
+/// When the level of a state is written to the pin
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PinWrites {
+    /// Write every state, even if the level does not change
+    EveryState,
+    /// Write only if the level changes, e.g. to save bus transactions of
+    /// GPIO expanders and shift registers
+    ///
+    /// An unchanged level is written again every `refresh` states for
+    /// robustness, never if `refresh` is zero.
+    EdgesOnly { refresh: u16 },
+}
+
+/// How `update()` and `update_by()` handle errors of the pin
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorPolicy {
+    /// Return the error; the failed state is driven again after a full
+    /// state duration
+    Propagate,
+    /// Swallow the error and drive the failed state again at the next
+    /// update, e.g. for pins behind an I2C GPIO expander
+    RetryNextTick,
+    /// Swallow the error and continue as if the state was driven
+    Ignore,
+    /// Return the error and stop the output until `clear_fault()`
+    LatchFault,
 }
 
 /// Output of blinking patterns on an LED
@@ -91,33 +245,33 @@ pub struct OnOffSequenceOutput<T: OutputPin> {
     /// The wrapped output pin.
     pub pin: T,
 
-    /// The update scaler: the clock rate at wich the output state changes
-    /// is equivalent the frequency of the update calls times *update_scale*
-    update_scale: u16,
-
-    /// The repeat configuration
-    repeat: Repeat,
-
     /// The output states are represented by the bits of an unsigned 128-bit integer
     output_states: u128,
 
-    /// How many bits are considered (min 1, max: 128)
-    number_of_output_states: u16,
+    /// Scaling, position and repetitions of the output states; the number
+    /// of states is how many bits are considered (min 1, max: 127)
+    progress: Progress,
 
-    /// Internal state: Manage scaling
-    scale_index: u16,
+    /// How missed state changes are handled by `update_by()`
+    catch_up: CatchUp,
 
-    /// internal state: Manage next output state
-    state_index: u16,
+    /// How pin errors are handled
+    error_policy: ErrorPolicy,
 
-    /// Internal state: Run output indicator
-    ///
-    /// # Values
-    ///
-    /// * true - either a run is not completed or there are more repetitions to do
-    /// * false - run is completed (intermediate) and no more repetitions are
-    ///   needed.
-    run_output: bool,
+    /// Number of pin errors, saturating
+    error_count: u32,
+
+    /// A pin error was latched, the output is stopped
+    faulted: bool,
+
+    /// When levels are written to the pin
+    pin_writes: PinWrites,
+
+    /// The level written last, None if unknown
+    driven_level: Option<bool>,
+
+    /// Number of states the driven level was kept without writing it
+    unwritten_states: u16,
 }
 
 impl<T: OutputPin> OnOffSequenceOutput<T> {
@@ -127,7 +281,7 @@ impl<T: OutputPin> OnOffSequenceOutput<T> {
     ///
     /// * `pin` - An as output initialized GPIO pin
     /// * `update_scale` - Scale factor:
-    ///      state change frequency = update frequency * update_scale
+    ///   state change frequency = update frequency * update_scale
     ///
     /// # Notes
     ///
@@ -135,20 +289,101 @@ impl<T: OutputPin> OnOffSequenceOutput<T> {
     pub fn new(pin: T, update_scale: u16) -> Self {
         Self {
             pin,
-            update_scale,
             output_states: 0b_10_u128,
-            number_of_output_states: 2,
-            repeat: Repeat::Forever,
-            scale_index: 0u16,
-            state_index: 0u16,
-            run_output: true,
+            progress: Progress::new(update_scale, 2, Repeat::Forever),
+            catch_up: CatchUp::ReplayEdges,
+            error_policy: ErrorPolicy::Propagate,
+            error_count: 0,
+            faulted: false,
+            pin_writes: PinWrites::EveryState,
+            driven_level: None,
+            unwritten_states: 0,
         }
     }
 
-    fn reinitialize_internal_state(&mut self) {
-        self.scale_index = 0u16;
-        self.state_index = 0u16;
-        self.run_output = true;
+    /// Select how `update_by()` catches up with missed state changes
+    ///
+    /// # Arguments
+    ///
+    /// * `catch_up` - The catch up policy, default is `CatchUp::ReplayEdges`
+    pub fn set_catch_up(&mut self, catch_up: CatchUp) {
+        self.catch_up = catch_up;
+    }
+
+    /// Select when levels are written to the pin
+    ///
+    /// The level of the pin is unknown afterwards, i.e. the next state is
+    /// written in any case.
+    ///
+    /// # Arguments
+    ///
+    /// * `pin_writes` - The write policy, default is `PinWrites::EveryState`
+    pub fn set_pin_writes(&mut self, pin_writes: PinWrites) {
+        self.pin_writes = pin_writes;
+        self.driven_level = None;
+    }
+
+    /// The level written to the pin last, true is high
+    ///
+    /// None if nothing was written yet, the write policy changed or the last
+    /// write failed.
+    pub fn driven_level(&self) -> Option<bool> {
+        self.driven_level
+    }
+
+    /// Select how pin errors are handled
+    ///
+    /// # Arguments
+    ///
+    /// * `error_policy` - The error policy, default is `ErrorPolicy::Propagate`
+    pub fn set_error_policy(&mut self, error_policy: ErrorPolicy) {
+        self.error_policy = error_policy;
+    }
+
+    /// The selected error policy
+    pub(crate) fn error_policy(&self) -> ErrorPolicy {
+        self.error_policy
+    }
+
+    /// Number of pin errors so far, regardless of the error policy
+    pub fn error_count(&self) -> u32 {
+        self.error_count
+    }
+
+    /// Restart counting pin errors from zero
+    pub fn reset_error_count(&mut self) {
+        self.error_count = 0;
+    }
+
+    /// Check if a pin error was latched by `ErrorPolicy::LatchFault`
+    ///
+    /// While faulted, updates neither drive the pin nor advance the output
+    /// and report it as completed.
+    pub fn is_faulted(&self) -> bool {
+        self.faulted
+    }
+
+    /// Resume a faulted output with the state which failed
+    pub fn clear_fault(&mut self) {
+        self.faulted = false;
+    }
+
+    /// Check if all states and repetitions are output, i.e. `update()`
+    /// returned true
+    pub fn is_completed(&self) -> bool {
+        self.progress.is_completed()
+    }
+
+    /// Check if the current state lasted its full duration, i.e. the next
+    /// update period starts a new state
+    pub(crate) fn at_state_boundary(&self) -> bool {
+        self.progress.at_state_boundary()
+    }
+
+    /// Check if the current run of the output states is completed, i.e. the
+    /// next update period starts a new repetition
+    pub(crate) fn at_repetition_boundary(&self) -> bool {
+        self.progress.at_repetition_boundary()
     }
 
     /// Set a new output
@@ -162,16 +397,48 @@ impl<T: OutputPin> OnOffSequenceOutput<T> {
     /// * `repeat` - How often is the pattern repeated
     ///
     pub fn set(&mut self, output_states: u128, number_of_output_states: u16, repeat: Repeat) {
-        if number_of_output_states > 127 {
-            panic!("Must be less than 128 output states");
-        };
-        if number_of_output_states == 0 {
-            panic!("Zero output states do not make sense");
-        };
-        self.output_states = output_states;
-        self.number_of_output_states = number_of_output_states;
-        self.repeat = repeat;
-        self.reinitialize_internal_state();
+        self.set_with_bit_order(
+            output_states,
+            number_of_output_states,
+            repeat,
+            BitOrder::LsbFirst,
+        );
+    }
+
+    /// Set a new output with selectable bit order
+    ///
+    /// # Arguments
+    ///
+    /// * `output_states` - bits of a unsigned number: 1 equals on; 0 equals off
+    /// * `number_of_output_states` - how many bits of the fixed number are
+    ///   considered to for the output state sequence counted from lsb
+    /// * `repeat` - How often is the pattern repeated
+    /// * `bit_order` - In which order the considered bits are played
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// // on for three states, then off - as on a timing diagram
+    /// ledout.set_with_bit_order(0b1110, 4, Repeat::Forever, BitOrder::MsbFirst);
+    /// // ... is equivalent to ...
+    /// // ledout.set(0b0111, 4, Repeat::Forever);
+    /// ```
+    pub fn set_with_bit_order(
+        &mut self,
+        output_states: u128,
+        number_of_output_states: u16,
+        repeat: Repeat,
+        bit_order: BitOrder,
+    ) {
+        assert_number_of_states(number_of_output_states);
+        self.output_states = bit_order.to_lsb_first(output_states, number_of_output_states);
+        event!(
+            debug,
+            "set {} output states, {:?}",
+            number_of_output_states,
+            repeat
+        );
+        self.progress.restart(number_of_output_states, repeat);
     }
 
     /// Set a new morse code as output
@@ -188,11 +455,70 @@ impl<T: OutputPin> OnOffSequenceOutput<T> {
     /// * with empty value if Ok()
     /// * or Err(MorseError)
     pub fn set_morse(&mut self, morse_text: &str, repeat: Repeat) -> Result<(), MorseError> {
-        let t = str_to_morse(morse_text)?;
+        let t = match str_to_morse(morse_text) {
+            Ok(t) => t,
+            Err(error) => {
+                event!(warn, "set_morse failed: {:?}", error);
+                return Err(error);
+            }
+        };
+        event!(debug, "set_morse {} output states, {:?}", t.1, repeat);
         self.output_states = t.0;
-        self.number_of_output_states = t.1;
-        self.reinitialize_internal_state();
-        self.repeat = repeat;
+        self.progress.restart(t.1, repeat);
+        Ok(())
+    }
+
+    /// Set a new output given in text notation
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern_text` - Sequence in the notation of the [`dsl`] module,
+    ///   e.g. `"#_##___"` or `"on 2, off 1 x3"`
+    /// * `repeat` - How often is the pattern repeated
+    ///
+    /// # Returns
+    ///
+    /// A result structure
+    ///
+    /// * with empty value if Ok()
+    /// * or Err(DslError)
+    pub fn set_dsl(&mut self, pattern_text: &str, repeat: Repeat) -> Result<(), DslError> {
+        let (output_states, number_of_output_states) = match dsl::parse(pattern_text) {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                event!(warn, "set_dsl failed: {:?}", error);
+                return Err(error);
+            }
+        };
+        self.set(output_states, number_of_output_states, repeat);
+        Ok(())
+    }
+
+    /// Set a new output given as pattern value, e.g. received from a host
+    ///
+    /// The update scale of the pattern replaces the one of the output, if
+    /// given. An active low pattern is output inverted.
+    ///
+    /// # Returns
+    ///
+    /// A result structure
+    ///
+    /// * with empty value if Ok()
+    /// * or Err(PatternError) if the number of output states is invalid
+    pub fn set_pattern(&mut self, pattern: &Pattern) -> Result<(), PatternError> {
+        pattern.validate()?;
+        if let Some(update_scale) = pattern.update_scale {
+            self.progress.update_scale = update_scale;
+        }
+        event!(
+            debug,
+            "set_pattern {} output states, {:?}",
+            pattern.number_of_output_states,
+            pattern.repeat
+        );
+        self.output_states = pattern.levels();
+        self.progress
+            .restart(pattern.number_of_output_states, pattern.repeat);
         Ok(())
     }
 }
@@ -206,60 +532,175 @@ fn state_at_position(states: u128, position: u16) -> bool {
     true
 }
 
+impl<T: OutputPin> OnOffSequenceOutput<T> {
+    /// Progress the output sequence by one state
+    ///
+    /// # Arguments
+    ///
+    /// * `drive` - whether the state is written to the pin
+    ///
+    /// # Returns
+    ///
+    /// The level of the processed state, or None if the output is completed
+    fn step(&mut self, drive: bool) -> Result<Option<bool>, T::Error> {
+        let mut level = None;
+
+        if let Some(state_index) = self.progress.current() {
+            // if we get here, always some output has to happen
+            let high = state_at_position(self.output_states, state_index);
+            event!(
+                trace,
+                "state {} of {}: {}",
+                state_index,
+                self.progress.number_of_states,
+                high
+            );
+            if drive {
+                self.drive(high)?;
+            }
+            level = Some(high);
+        }
+        self.progress.advance();
+
+        Ok(level)
+    }
+
+    /// Drive the current state and advance over all following states of the
+    /// same level within the current run
+    ///
+    /// # Returns
+    ///
+    /// The number of states covered by the driven level, or None if the
+    /// output is completed
+    pub(crate) fn advance_run(&mut self) -> Result<Option<u16>, T::Error> {
+        if self.faulted {
+            return Ok(None);
+        }
+        let state_index = match self.progress.current() {
+            Some(state_index) => state_index,
+            None => return Ok(None),
+        };
+        let high = state_at_position(self.output_states, state_index);
+        let mut count = 1;
+        while state_index + count < self.progress.number_of_states
+            && state_at_position(self.output_states, state_index + count) == high
+        {
+            count += 1;
+        }
+        self.drive(high)?;
+        for _ in 0..count {
+            self.progress.advance();
+        }
+        self.progress.scale_index = 0;
+        Ok(Some(count))
+    }
+
+    fn drive(&mut self, high: bool) -> Result<(), T::Error> {
+        if let PinWrites::EdgesOnly { refresh } = self.pin_writes {
+            if self.driven_level == Some(high)
+                && (refresh == 0 || self.unwritten_states + 1 < refresh)
+            {
+                self.unwritten_states = self.unwritten_states.saturating_add(1);
+                return Ok(());
+            }
+        }
+        self.unwritten_states = 0;
+        let result = if high {
+            self.pin.set_high()
+        } else {
+            self.pin.set_low()
+        };
+        let error = match result {
+            Ok(()) => {
+                self.driven_level = Some(high);
+                return Ok(());
+            }
+            Err(error) => error,
+        };
+        self.driven_level = None;
+        event!(warn, "pin error driving {}", high);
+        self.error_count = self.error_count.saturating_add(1);
+        match self.error_policy {
+            ErrorPolicy::Ignore => Ok(()),
+            ErrorPolicy::LatchFault => {
+                self.faulted = true;
+                Err(error)
+            }
+            ErrorPolicy::Propagate | ErrorPolicy::RetryNextTick => Err(error),
+        }
+    }
+
+    /// Handle a pin error returned by `drive()` for `update()`
+    fn pin_error(&mut self, error: T::Error) -> Result<bool, T::Error> {
+        match self.error_policy {
+            ErrorPolicy::RetryNextTick => {
+                self.progress.retry_next_tick();
+                Ok(false)
+            }
+            _ => Err(error),
+        }
+    }
+}
+
 impl<T: OutputPin> OutputUpdate for OnOffSequenceOutput<T> {
     type Error = T::Error;
 
     /// Updates the output logic and potentially switches the LED state
     fn update(&mut self) -> Result<bool, Self::Error> {
+        if self.faulted {
+            return Ok(true);
+        }
         // handle the update scale
-        self.scale_index += 1;
-        if self.update_scale > self.scale_index {
-            return Ok(!self.run_output);
+        if !self.progress.tick() {
+            return Ok(self.progress.is_completed());
         }
-        self.scale_index = 0;
 
-        // handle the output sequence
-        if self.run_output {
-            // if we get here, always some output has to happen
-            if state_at_position(self.output_states, self.state_index) {
-                self.pin.set_high()?;
-            } else {
-                self.pin.set_low()?;
-            }
-            self.state_index += 1;
-            if self.state_index >= self.number_of_output_states {
-                // all states are "printed"
-                self.run_output = false;
-                self.state_index = 0;
-            } else {
-            }
+        if let Err(error) = self.step(true) {
+            return self.pin_error(error);
         }
+        Ok(self.progress.is_completed())
+    }
 
-        // handle the repetitions
-        if !self.run_output {
-            self.repeat = match self.repeat {
-                Repeat::Never => Repeat::Never,
-                Repeat::Forever => Repeat::Forever,
-                Repeat::Times(n) => {
-                    if n > 0 {
-                        Repeat::Times(n - 1)
-                    } else {
-                        Repeat::Never
+    /// Updates the output logic for a number of elapsed update periods,
+    /// missed state changes are handled as selected by `set_catch_up()`
+    ///
+    /// # Notes
+    ///
+    /// * With `CatchUp::ReplayEdges` a pin error ends the burst; the failed
+    ///   state is handled by the error policy, the states due after it in
+    ///   this call are not caught up, i.e. the output lags behind by them
+    fn update_by(&mut self, ticks: u16) -> Result<bool, Self::Error> {
+        if self.faulted {
+            return Ok(true);
+        }
+        // handle the update scale
+        let steps = self.progress.ticks(ticks);
+
+        let mut last_level = None;
+        for _ in 0..steps {
+            match self.catch_up {
+                CatchUp::ReplayEdges => {
+                    if let Err(error) = self.step(true) {
+                        return self.pin_error(error);
                     }
                 }
-            };
-            self.run_output = match self.repeat {
-                Repeat::Never => false,
-                Repeat::Forever => true,
-                Repeat::Times(_) => true,
-            };
+                CatchUp::SkipToCurrent => {
+                    if let Some(level) = self.step(false)? {
+                        last_level = Some(level);
+                    }
+                }
+            }
         }
-
-        Ok(!self.run_output)
+        if let Some(high) = last_level {
+            if let Err(error) = self.drive(high) {
+                return self.pin_error(error);
+            }
+        }
+        Ok(self.progress.is_completed())
     }
 }
 
-/// Determine at position of the most left one in the bitfield represented as u128
+/// Determine the position of the most left one in the bitfield represented as u128
 ///
 /// # Arguments
 ///
@@ -267,29 +708,66 @@ impl<T: OutputPin> OutputUpdate for OnOffSequenceOutput<T> {
 ///
 /// # Returns
 ///
-/// *
-pub fn position_of_highest_one(bitfield: u128) -> u16 {
-    const MSB_ONE: u128 = 1 << 127;
-
-    let mut position = 127_u16;
-    let mut bitfield = bitfield;
-    while (bitfield & MSB_ONE) == 0 && position > 0 {
-        bitfield = bitfield << 1;
-        position -= 1;
+/// * Some(position) - position of the most left one counted from lsb (0..=127)
+/// * None - if no bit is set at all
+pub const fn position_of_highest_one(bitfield: u128) -> Option<u16> {
+    if bitfield == 0 {
+        return None;
+    }
+    Some(127 - bitfield.leading_zeros() as u16)
+}
+
+/// Determine the number of output states of a bitfield
+///
+/// The output sequence ends with the most left one of the bitfield, followed
+/// by the given number of off states. The off states cannot be expressed by
+/// the bitfield itself, because they are leading zeros.
+///
+/// # Arguments
+///
+/// * `bitfield` - The output states, processed from lsb to msb
+/// * `trailing_off_states` - Number of off states appended after the most
+///   left one
+///
+/// # Returns
+///
+/// The number of output states to be used with `set()`; it is zero for a zero
+/// bitfield without trailing off states, which `set()` rejects. It saturates
+/// at `u16::MAX`, so out of range counts are rejected by `set()` as well.
+///
+/// # Examples
+///
+/// ```rust
+/// use on_off_sequence_output::number_of_states;
+///
+/// assert_eq!(4, number_of_states(0b1100, 0));
+/// assert_eq!(6, number_of_states(0b1100, 2));
+/// assert_eq!(3, number_of_states(0, 3));
+/// ```
+pub const fn number_of_states(bitfield: u128, trailing_off_states: u16) -> u16 {
+    match position_of_highest_one(bitfield) {
+        Some(position) => (position + 1).saturating_add(trailing_off_states),
+        None => trailing_off_states,
     }
-    position
 }
 
 pub mod macros {
     /// Simplified setting of the output without repetitions
     ///
-    /// The number of the output states is automatically computed
-    /// It requires that the last output state equals to one
+    /// The number of the output states is automatically computed by
+    /// [`number_of_states()`](../fn.number_of_states.html): The sequence ends
+    /// with the most left one of the bitfield, optionally followed by a
+    /// number of off states.
     ///
     /// # Arguments
     ///
     /// * `Instance of OnOffSequenceOutput`
     /// * `bitfield (u128)` - MSB of the Output sequence must be one
+    /// * `trailing_off_states (u16)` (optional) - off states after the MSB
+    ///
+    /// # Panics
+    ///
+    /// Like `set()`, if the resulting number of states is zero or exceeds 127
     ///
     /// # Examples
     ///
@@ -297,36 +775,66 @@ pub mod macros {
     /// set_output_once!(ledout, 0b1100);
     /// // ... is equivalent to ...
     /// // ledout.set(0b1100, 4, Repeat::Never);
+    ///
+    /// set_output_once!(ledout, 0b1100, 2);
+    /// // ... is equivalent to ...
+    /// // ledout.set(0b1100, 6, Repeat::Never);
     /// ```
     #[macro_export]
     macro_rules! set_output_once {
         ($a:expr, $b:expr) => {
-            $a.set($b, position_of_highest_one($b), Repeat::Never)
+            $crate::set_output_once!($a, $b, 0)
         };
+        ($a:expr, $b:expr, $trailing:expr) => {{
+            let bitfield: u128 = $b;
+            $a.set(
+                bitfield,
+                $crate::number_of_states(bitfield, $trailing),
+                $crate::Repeat::Never,
+            )
+        }};
     }
 
     /// Simplified setting of the output with infinite repetitions
     ///
-    /// The number of the output states is automatically computed
-    /// It requires that the last output state equals to one
+    /// The number of the output states is automatically computed by
+    /// [`number_of_states()`](../fn.number_of_states.html): The sequence ends
+    /// with the most left one of the bitfield, optionally followed by a
+    /// number of off states.
     ///
     /// # Arguments
     ///
     /// * `Instance of OnOffSequenceOutput`
     /// * `bitfield (u128)` - MSB of the Output sequence must be one
+    /// * `trailing_off_states (u16)` (optional) - off states after the MSB
+    ///
+    /// # Panics
+    ///
+    /// Like `set()`, if the resulting number of states is zero or exceeds 127
     ///
     /// # Examples
     /// ```rust,ignore
     /// set_output_forever!(ledout, 0b1000);
     /// // ... is equivalent to ...
     /// // ledout.set(0b1000, 4, Repeat::Forever);
+    ///
+    /// set_output_forever!(ledout, 0b1, 3);
+    /// // ... is equivalent to ...
+    /// // ledout.set(0b1, 4, Repeat::Forever);
     /// ```
-
     #[macro_export]
     macro_rules! set_output_forever {
         ($a:expr, $b:expr) => {
-            $a.set($b, position_of_highest_one($b), Repeat::Forever)
+            $crate::set_output_forever!($a, $b, 0)
         };
+        ($a:expr, $b:expr, $trailing:expr) => {{
+            let bitfield: u128 = $b;
+            $a.set(
+                bitfield,
+                $crate::number_of_states(bitfield, $trailing),
+                $crate::Repeat::Forever,
+            )
+        }};
     }
 }
 
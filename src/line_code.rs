@@ -0,0 +1,304 @@
+//! Line codes for optical data transmission, e.g. device IDs to a phone
+//! camera or a photodiode
+//!
+//! Data bytes are framed and encoded into output states:
+//!
+//! * `start` on states mark the start of a frame
+//! * the data bytes follow, LSB first, each bit encoded by the line code
+//! * an optional CRC-8 of the data bytes follows like a data byte
+//! * `stop` off states separate repeated frames
+//!
+//! The line codes are
+//!
+//! * [`LineCode::Nrz`] - one state per bit, on for 1
+//! * [`LineCode::Manchester`] - two states per bit, IEEE 802.3: on-off for
+//!   0, off-on for 1; at most two equal states in a row, so a start of three
+//!   on states is unique
+//! * [`LineCode::DifferentialManchester`] - two states per bit, always a
+//!   transition in the middle of a bit, an additional one at the start of
+//!   a 0; independent of the polarity of the receiver
+//!
+//! Decoders take the sampled states, one sample per state. Off states
+//! before the frame, e.g. idle time of the receiver, are skipped.
+//!
+//! # Example
+//!
+//! ```rust
+//! use on_off_sequence_output::line_code::*;
+//!
+//! let config = LineCodeConfig::default();
+//! let (states, len) = encode(&[0x42, 0x17], &config).unwrap();
+//! assert_eq!(3 + 2 * 16 + 3, len);
+//!
+//! let mut buffer = [0_u8; 4];
+//! assert_eq!(Ok(&[0x42, 0x17][..]), decode(states, len, &config, &mut buffer));
+//! ```
+
+use crate::builder::{SequenceBuilder, SequenceTooLong};
+
+/// Line code conversion errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineCodeError {
+    /// The frame has more than 127 states
+    SequenceTooLong,
+    /// The frame needs at least one start state
+    InvalidConfig,
+    /// The states are not a frame of the given configuration
+    MalformedCode,
+    /// The CRC does not match the decoded data
+    CrcMismatch,
+    /// The decoded data does not fit into the buffer
+    BufferTooSmall,
+}
+
+/// How bits are encoded into states
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineCode {
+    /// Non-return-to-zero, one state per bit
+    Nrz,
+    /// Manchester as of IEEE 802.3, two states per bit
+    Manchester,
+    /// Differential Manchester, two states per bit
+    DifferentialManchester,
+}
+
+impl LineCode {
+    /// Number of states of a bit
+    const fn states_per_bit(self) -> u16 {
+        match self {
+            LineCode::Nrz => 1,
+            LineCode::Manchester | LineCode::DifferentialManchester => 2,
+        }
+    }
+}
+
+/// Framing and line code of a frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineCodeConfig {
+    /// How bits are encoded
+    pub code: LineCode,
+    /// On states marking the start of a frame, at least one
+    pub start: u16,
+    /// Off states ending a frame
+    pub stop: u16,
+    /// Whether a CRC-8 follows the data bytes, see [`crc8()`]
+    pub crc: bool,
+}
+
+impl Default for LineCodeConfig {
+    /// Manchester code with three start and three stop states, without CRC
+    fn default() -> Self {
+        Self {
+            code: LineCode::Manchester,
+            start: 3,
+            stop: 3,
+            crc: false,
+        }
+    }
+}
+
+/// CRC-8 with polynomial 0x07 and initial value 0, as used by SMBus
+pub const fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0_u8;
+    let mut index = 0;
+    while index < data.len() {
+        crc ^= data[index];
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        index += 1;
+    }
+    crc
+}
+
+impl From<SequenceTooLong> for LineCodeError {
+    fn from(_: SequenceTooLong) -> Self {
+        LineCodeError::SequenceTooLong
+    }
+}
+
+/// Appends the states of a byte, LSB first
+fn push_byte(builder: &mut SequenceBuilder, byte: u8, code: LineCode) -> Result<(), LineCodeError> {
+    for bit in 0..8 {
+        let one = (byte >> bit) & 1 == 1;
+        match code {
+            LineCode::Nrz => builder.push(one, 1)?,
+            LineCode::Manchester => {
+                builder.push(!one, 1)?;
+                builder.push(one, 1)?;
+            }
+            LineCode::DifferentialManchester => {
+                let first = builder.last() == one;
+                builder.push(first, 1)?;
+                builder.push(!first, 1)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Data bytes to a frame of output states
+///
+/// # Arguments
+///
+/// * `data` - Bytes to transmit, sent first to last
+/// * `config` - Framing and line code
+///
+/// # Returns
+///
+/// A result with Ok(tuple) as
+///
+/// * `u128` - a sequence of output states encoded into bits of a u128
+/// * `u16`  - length of the sequence of output states
+///
+/// or Err(LineCodeError)
+pub fn encode(data: &[u8], config: &LineCodeConfig) -> Result<(u128, u16), LineCodeError> {
+    if config.start == 0 {
+        return Err(LineCodeError::InvalidConfig);
+    }
+    let mut builder = SequenceBuilder::new();
+    builder.push(true, config.start)?;
+    for byte in data {
+        push_byte(&mut builder, *byte, config.code)?;
+    }
+    if config.crc {
+        push_byte(&mut builder, crc8(data), config.code)?;
+    }
+    builder.push(false, config.stop)?;
+    Ok((builder.sequence, builder.len))
+}
+
+/// Sampled output states of a frame to data bytes
+///
+/// # Arguments
+///
+/// * `states` - Sampled states, processed from lsb to msb
+/// * `len` - Number of sampled states, the frame ends with the last one
+/// * `config` - Framing and line code of the frame
+/// * `buffer` - Storage for the decoded data bytes
+///
+/// # Returns
+///
+/// A result with Ok(data bytes) without the CRC, or Err(LineCodeError)
+pub fn decode<'a>(
+    states: u128,
+    len: u16,
+    config: &LineCodeConfig,
+    buffer: &'a mut [u8],
+) -> Result<&'a [u8], LineCodeError> {
+    if config.start == 0 {
+        return Err(LineCodeError::InvalidConfig);
+    }
+    if len > 128 {
+        return Err(LineCodeError::SequenceTooLong);
+    }
+    let state = |position: u16| (states >> position) & 1 == 1;
+
+    // idle off states, start and stop states
+    let mut position = 0;
+    while position < len && !state(position) {
+        position += 1;
+    }
+    if len - position < config.start.saturating_add(config.stop) {
+        return Err(LineCodeError::MalformedCode);
+    }
+    for _ in 0..config.start {
+        if !state(position) {
+            return Err(LineCodeError::MalformedCode);
+        }
+        position += 1;
+    }
+    let end = len - config.stop;
+    if (end..len).any(state) {
+        return Err(LineCodeError::MalformedCode);
+    }
+
+    let states_per_byte = 8 * config.code.states_per_bit();
+    // `is_multiple_of()` needs a recent toolchain
+    #[allow(clippy::manual_is_multiple_of)]
+    if (end - position) % states_per_byte != 0 {
+        return Err(LineCodeError::MalformedCode);
+    }
+    let number_of_bytes = usize::from((end - position) / states_per_byte);
+    let number_of_data_bytes = if config.crc {
+        number_of_bytes
+            .checked_sub(1)
+            .ok_or(LineCodeError::MalformedCode)?
+    } else {
+        number_of_bytes
+    };
+    if number_of_data_bytes > buffer.len() {
+        return Err(LineCodeError::BufferTooSmall);
+    }
+
+    let mut reader = Reader {
+        states,
+        position,
+        // the start states are on
+        previous: true,
+        code: config.code,
+    };
+    for byte in buffer[..number_of_data_bytes].iter_mut() {
+        *byte = reader.byte()?;
+    }
+    let data = &buffer[..number_of_data_bytes];
+    if config.crc && crc8(data) != reader.byte()? {
+        return Err(LineCodeError::CrcMismatch);
+    }
+    Ok(data)
+}
+
+/// Decodes the bytes of a frame
+struct Reader {
+    states: u128,
+    /// The next state to decode
+    position: u16,
+    /// The level of the last decoded state
+    previous: bool,
+    code: LineCode,
+}
+
+impl Reader {
+    fn state(&self, offset: u16) -> bool {
+        (self.states >> (self.position + offset)) & 1 == 1
+    }
+
+    fn bit(&mut self) -> Result<bool, LineCodeError> {
+        let one = match self.code {
+            LineCode::Nrz => self.state(0),
+            LineCode::Manchester | LineCode::DifferentialManchester => {
+                let (first, second) = (self.state(0), self.state(1));
+                if first == second {
+                    return Err(LineCodeError::MalformedCode);
+                }
+                let one = if self.code == LineCode::Manchester {
+                    second
+                } else {
+                    first == self.previous
+                };
+                self.previous = second;
+                one
+            }
+        };
+        self.position += self.code.states_per_bit();
+        Ok(one)
+    }
+
+    /// Decodes a byte, LSB first
+    fn byte(&mut self) -> Result<u8, LineCodeError> {
+        let mut byte = 0_u8;
+        for bit in 0..8 {
+            byte |= u8::from(self.bit()?) << bit;
+        }
+        Ok(byte)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,170 @@
+/// Tests for the line codes
+use super::*;
+use crate::dsl::parse;
+
+fn config(code: LineCode, start: u16, stop: u16, crc: bool) -> LineCodeConfig {
+    LineCodeConfig {
+        code,
+        start,
+        stop,
+        crc,
+    }
+}
+
+#[test]
+fn encode_nrz() {
+    let config = config(LineCode::Nrz, 1, 2, false);
+    assert_eq!(
+        Ok(parse("# #_#__#_# __").unwrap()),
+        encode(&[0b1010_0101], &config)
+    );
+}
+
+#[test]
+fn encode_manchester() {
+    let config = config(LineCode::Manchester, 1, 1, false);
+    assert_eq!(
+        Ok(parse("# _# #_#_#_#_#_#_#_ _").unwrap()),
+        encode(&[0x01], &config)
+    );
+}
+
+#[test]
+fn encode_differential_manchester() {
+    let config = config(LineCode::DifferentialManchester, 1, 1, false);
+    assert_eq!(
+        Ok(parse("# _#_#_#_#_#_#_#_# _").unwrap()),
+        encode(&[0x00], &config)
+    );
+    assert_eq!(
+        Ok(parse("# #__##__##__##__# _").unwrap()),
+        encode(&[0xff], &config)
+    );
+}
+
+#[test]
+fn encode_crc() {
+    let config = config(LineCode::Nrz, 1, 0, true);
+    let (states, len) = encode(&[0x01], &config).unwrap();
+    assert_eq!(1 + 8 + 8, len);
+    assert_eq!(u128::from(crc8(&[0x01])), states >> 9);
+}
+
+#[test]
+fn crc8_check_value() {
+    assert_eq!(0xf4, crc8(b"123456789"));
+    assert_eq!(0x00, crc8(&[]));
+}
+
+#[test]
+fn encode_errors() {
+    assert_eq!(
+        Err(LineCodeError::InvalidConfig),
+        encode(&[0x00], &config(LineCode::Nrz, 0, 1, false))
+    );
+    assert_eq!(
+        Err(LineCodeError::SequenceTooLong),
+        encode(&[0; 16], &config(LineCode::Nrz, 1, 0, false))
+    );
+    assert_eq!(
+        Err(LineCodeError::SequenceTooLong),
+        encode(&[0; 7], &config(LineCode::Manchester, 3, 3, true))
+    );
+    assert_eq!(
+        Err(LineCodeError::SequenceTooLong),
+        encode(&[0x00], &config(LineCode::Manchester, 3, u16::MAX, false))
+    );
+    assert_eq!(
+        Err(LineCodeError::SequenceTooLong),
+        encode(&[], &config(LineCode::Nrz, u16::MAX, 0, false))
+    );
+}
+
+#[test]
+fn round_trip() {
+    let data = [0x00, 0xff, 0x42, 0x17, 0xa5];
+    for code in [
+        LineCode::Nrz,
+        LineCode::Manchester,
+        LineCode::DifferentialManchester,
+    ] {
+        for crc in [false, true] {
+            let config = config(code, 3, 3, crc);
+            let (states, len) = encode(&data, &config).unwrap();
+            let mut buffer = [0_u8; 8];
+            assert_eq!(Ok(&data[..]), decode(states, len, &config, &mut buffer));
+        }
+    }
+}
+
+#[test]
+fn round_trip_after_idle_states() {
+    let config = LineCodeConfig::default();
+    let (states, len) = encode(&[0x5a], &config).unwrap();
+    let mut buffer = [0_u8; 1];
+    assert_eq!(
+        Ok(&[0x5a][..]),
+        decode(states << 10, len + 10, &config, &mut buffer)
+    );
+}
+
+#[test]
+fn round_trip_without_data() {
+    let config = config(LineCode::Manchester, 1, 1, true);
+    let (states, len) = encode(&[], &config).unwrap();
+    let mut buffer = [0_u8; 0];
+    assert_eq!(Ok(&[][..]), decode(states, len, &config, &mut buffer));
+}
+
+#[test]
+fn decode_malformed() {
+    let mut buffer = [0_u8; 4];
+    let config = config(LineCode::Manchester, 3, 3, false);
+    let (states, len) = encode(&[0x00], &config).unwrap();
+    // two equal halves of a bit
+    assert_eq!(
+        Err(LineCodeError::MalformedCode),
+        decode(states | 1 << 4, len, &config, &mut buffer)
+    );
+    // stop state on
+    assert_eq!(
+        Err(LineCodeError::MalformedCode),
+        decode(states | 1 << (len - 1), len, &config, &mut buffer)
+    );
+    // truncated
+    assert_eq!(
+        Err(LineCodeError::MalformedCode),
+        decode(states, len - 1, &config, &mut buffer)
+    );
+    // start too short
+    assert_eq!(
+        Err(LineCodeError::MalformedCode),
+        decode(0b011, 3, &config, &mut buffer)
+    );
+    assert_eq!(
+        Err(LineCodeError::MalformedCode),
+        decode(0, 0, &config, &mut buffer)
+    );
+}
+
+#[test]
+fn decode_crc_mismatch() {
+    let config = config(LineCode::Nrz, 1, 1, true);
+    let (states, len) = encode(&[0x00, 0x00], &config).unwrap();
+    let mut buffer = [0_u8; 4];
+    assert_eq!(
+        Err(LineCodeError::CrcMismatch),
+        decode(states | 1 << 3, len, &config, &mut buffer)
+    );
+}
+
+#[test]
+fn decode_buffer_too_small() {
+    let config = LineCodeConfig::default();
+    let (states, len) = encode(&[0x01, 0x02], &config).unwrap();
+    let mut buffer = [0_u8; 1];
+    assert_eq!(
+        Err(LineCodeError::BufferTooSmall),
+        decode(states, len, &config, &mut buffer)
+    );
+}
@@ -0,0 +1,245 @@
+//! Multiplexed and charlieplexed LED matrices
+//!
+//! Boards with few GPIOs drive many LEDs by multiplexing: only one row of
+//! the matrix is lit at a time, `update()` switches to the next row. Each
+//! LED runs its own on/off sequence with the scaling and repetitions of
+//! `OnOffSequenceOutput`; the update scale applies to every LED.
+//!
+//! As every update lights one row only, the multiplex rate is the update
+//! frequency divided by the number of rows; it should be high enough to
+//! avoid flicker, e.g. 100 Hz or more.
+//!
+//! * [`MultiplexedMatrix`] - row/column multiplexing: a row pin driven high
+//!   selects the row, a column pin driven low lights the LED of the selected
+//!   row.
+//! * [`CharlieplexedMatrix`] - charlieplexing of `N` tri-state pins driving
+//!   `N * (N - 1)` LEDs, one LED between every ordered pair of pins from
+//!   anode to cathode.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use on_off_sequence_output::matrix::MultiplexedMatrix;
+//! use on_off_sequence_output::prelude::*;
+//!
+//! let mut matrix = MultiplexedMatrix::new([row0, row1], [col0, col1, col2], UPDATE_SCALE);
+//! matrix.set(0, 2, 0b0101, 4, Repeat::Forever);
+//! matrix.set(1, 0, 0b0011, 4, Repeat::Times(3));
+//! loop {
+//!    matrix.update().unwrap();
+//!    wait(1.ms());
+//! }
+//! ```
+
+use crate::progress::LedSequence;
+use crate::{OutputUpdate, Repeat};
+use embedded_hal::digital::v2::OutputPin;
+
+/// A pin which can be switched off completely, e.g. by switching it to an
+/// input
+///
+/// Needed for charlieplexing, driving the pin high or low makes it an
+/// output again.
+pub trait TriStatePin: OutputPin {
+    /// Switches the pin to high impedance
+    fn set_floating(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Row/column multiplexed LED matrix
+pub struct MultiplexedMatrix<P: OutputPin, const ROWS: usize, const COLUMNS: usize> {
+    /// The row pins, high selects a row
+    pub rows: [P; ROWS],
+
+    /// The column pins, low lights the LED in the selected row
+    pub columns: [P; COLUMNS],
+
+    /// The sequences of the LEDs by row and column
+    leds: [[LedSequence; COLUMNS]; ROWS],
+
+    /// The row currently selected
+    scan_row: usize,
+}
+
+impl<P: OutputPin, const ROWS: usize, const COLUMNS: usize> MultiplexedMatrix<P, ROWS, COLUMNS> {
+    /// Initializes a new multiplexed matrix
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - As output initialized GPIO pins, low
+    /// * `columns` - As output initialized GPIO pins
+    /// * `update_scale` - Scale factor of every LED:
+    ///   led state change frequency = update frequency * update_scale
+    ///
+    /// # Notes
+    ///
+    /// * Default is all LEDs off
+    ///
+    /// # Panics
+    ///
+    /// If there are no rows or no columns
+    pub fn new(rows: [P; ROWS], columns: [P; COLUMNS], update_scale: u16) -> Self {
+        if ROWS == 0 || COLUMNS == 0 {
+            panic!("Must be at least 1 row and 1 column");
+        };
+        Self {
+            rows,
+            columns,
+            leds: [[LedSequence::off(update_scale); COLUMNS]; ROWS],
+            // the first update selects row 0
+            scan_row: ROWS - 1,
+        }
+    }
+
+    /// Set a new output of a single LED, see `OnOffSequenceOutput::set()`
+    ///
+    /// # Panics
+    ///
+    /// If row or column are out of range, or on the number of output states
+    /// like `set()` does
+    pub fn set(
+        &mut self,
+        row: usize,
+        column: usize,
+        output_states: u128,
+        number_of_output_states: u16,
+        repeat: Repeat,
+    ) {
+        self.leds[row][column].set(output_states, number_of_output_states, repeat);
+    }
+}
+
+impl<P: OutputPin, const ROWS: usize, const COLUMNS: usize> OutputUpdate
+    for MultiplexedMatrix<P, ROWS, COLUMNS>
+{
+    type Error = P::Error;
+
+    /// Progresses the sequences of all LEDs and selects the next row
+    ///
+    /// # Returns
+    ///
+    /// true if the sequences of all LEDs are completed; the matrix still
+    /// needs updates to stay lit
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        let mut completed = true;
+        for led in self.leds.iter_mut().flatten() {
+            led.tick();
+            completed &= led.progress.is_completed();
+        }
+
+        self.rows[self.scan_row].set_low()?;
+        self.scan_row = (self.scan_row + 1) % ROWS;
+        for (column, led) in self.columns.iter_mut().zip(self.leds[self.scan_row].iter()) {
+            if led.lit {
+                column.set_low()?;
+            } else {
+                column.set_high()?;
+            }
+        }
+        self.rows[self.scan_row].set_high()?;
+        Ok(completed)
+    }
+}
+
+/// Charlieplexed LED matrix
+pub struct CharlieplexedMatrix<P: TriStatePin, const N: usize> {
+    /// The pins, every pin is the anode of `N - 1` LEDs
+    pub pins: [P; N],
+
+    /// The sequences of the LEDs by anode and cathode, the diagonal is
+    /// unused
+    leds: [[LedSequence; N]; N],
+
+    /// The anode currently driven
+    scan_anode: usize,
+}
+
+impl<P: TriStatePin, const N: usize> CharlieplexedMatrix<P, N> {
+    /// Initializes a new charlieplexed matrix
+    ///
+    /// # Arguments
+    ///
+    /// * `pins` - Tri-state GPIO pins, floating
+    /// * `update_scale` - Scale factor of every LED:
+    ///   led state change frequency = update frequency * update_scale
+    ///
+    /// # Notes
+    ///
+    /// * Default is all LEDs off
+    ///
+    /// # Panics
+    ///
+    /// If there are less than 2 pins, i.e. no LED
+    pub fn new(pins: [P; N], update_scale: u16) -> Self {
+        if N < 2 {
+            panic!("Must be at least 2 pins");
+        };
+        Self {
+            pins,
+            leds: [[LedSequence::off(update_scale); N]; N],
+            // the first update drives pin 0
+            scan_anode: N - 1,
+        }
+    }
+
+    /// Set a new output of the LED from `anode` to `cathode`, see
+    /// `OnOffSequenceOutput::set()`
+    ///
+    /// # Panics
+    ///
+    /// If anode or cathode are out of range or equal, or on the number of
+    /// output states like `set()` does
+    pub fn set(
+        &mut self,
+        anode: usize,
+        cathode: usize,
+        output_states: u128,
+        number_of_output_states: u16,
+        repeat: Repeat,
+    ) {
+        if anode == cathode {
+            panic!("There is no LED from a pin to itself");
+        };
+        self.leds[anode][cathode].set(output_states, number_of_output_states, repeat);
+    }
+}
+
+impl<P: TriStatePin, const N: usize> OutputUpdate for CharlieplexedMatrix<P, N> {
+    type Error = P::Error;
+
+    /// Progresses the sequences of all LEDs and drives the next anode
+    ///
+    /// # Returns
+    ///
+    /// true if the sequences of all LEDs are completed; the matrix still
+    /// needs updates to stay lit
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        let mut completed = true;
+        for (anode, leds) in self.leds.iter_mut().enumerate() {
+            for (cathode, led) in leds.iter_mut().enumerate() {
+                if anode != cathode {
+                    led.tick();
+                    completed &= led.progress.is_completed();
+                }
+            }
+        }
+
+        self.pins[self.scan_anode].set_floating()?;
+        self.scan_anode = (self.scan_anode + 1) % N;
+        let anode = self.scan_anode;
+        for (cathode, pin) in self.pins.iter_mut().enumerate() {
+            if cathode == anode {
+                continue;
+            }
+            if self.leds[anode][cathode].lit {
+                pin.set_low()?;
+            } else {
+                pin.set_floating()?;
+            }
+        }
+        self.pins[anode].set_high()?;
+        Ok(completed)
+    }
+}
+
+#[cfg(test)]
+mod tests;
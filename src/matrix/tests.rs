@@ -0,0 +1,161 @@
+/// Tests for the LED matrices
+use super::*;
+use crate::tests::mocks::*;
+
+/// Records the levels driven: `H`igh, `L`ow or `Z` floating
+#[derive(Default)]
+struct MockedTriStatePin {
+    levels: [u8; 8],
+    len: usize,
+}
+
+impl MockedTriStatePin {
+    fn record(&mut self, level: u8) -> Result<(), MockedOutputPinError> {
+        self.levels[self.len] = level;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn levels(&self) -> &[u8] {
+        &self.levels[..self.len]
+    }
+}
+
+impl OutputPin for MockedTriStatePin {
+    type Error = MockedOutputPinError;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.record(b'H')
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.record(b'L')
+    }
+}
+
+impl TriStatePin for MockedTriStatePin {
+    fn set_floating(&mut self) -> Result<(), Self::Error> {
+        self.record(b'Z')
+    }
+}
+
+mod multiplexed {
+    use super::*;
+
+    #[test]
+    fn scan_rows() -> Result<(), MockedOutputPinError> {
+        let rows = [
+            MockedOutputPin::expected(2, 0b01_u128),
+            MockedOutputPin::expected(2, 0b10_u128),
+        ];
+        let columns = [
+            MockedOutputPin::expected(2, 0b10_u128),
+            MockedOutputPin::expected(2, 0b01_u128),
+        ];
+        let mut matrix = MultiplexedMatrix::new(rows, columns, 1);
+        matrix.set(0, 0, 0b1, 1, Repeat::Never);
+        matrix.set(1, 1, 0b10, 2, Repeat::Never);
+        assert!(!matrix.update()?);
+        assert!(matrix.update()?);
+        Ok(())
+    }
+
+    #[test]
+    fn own_sequence_per_led() -> Result<(), MockedOutputPinError> {
+        // single row: the columns show the sequences directly (low is lit)
+        let rows = [MockedOutputPin::expected(8, 0b1010_1010_u128)];
+        let columns = [
+            MockedOutputPin::expected(4, 0b1010_u128),
+            MockedOutputPin::expected(4, 0b0000_u128),
+        ];
+        let mut matrix = MultiplexedMatrix::new(rows, columns, 1);
+        matrix.set(0, 0, 0b01, 2, Repeat::Forever);
+        matrix.set(0, 1, 0b11, 2, Repeat::Never);
+        for _ in 0..4 {
+            assert!(!matrix.update()?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_range() {
+        let rows = [MockedOutputPin::expected(0, 0b0_u128)];
+        let columns = [MockedOutputPin::expected(0, 0b0_u128)];
+        let mut matrix = MultiplexedMatrix::new(rows, columns, 1);
+        matrix.set(0, 1, 0b1, 1, Repeat::Never);
+    }
+
+    #[test]
+    #[should_panic(expected = "Must be at least 1 row and 1 column")]
+    fn no_rows() {
+        let columns = [MockedOutputPin::expected(0, 0b0_u128)];
+        let _ = MultiplexedMatrix::<MockedOutputPin, 0, 1>::new([], columns, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Must be at least 1 row and 1 column")]
+    fn no_columns() {
+        let rows = [MockedOutputPin::expected(0, 0b0_u128)];
+        let _ = MultiplexedMatrix::<MockedOutputPin, 1, 0>::new(rows, [], 1);
+    }
+}
+
+mod charlieplexed {
+    use super::*;
+
+    #[test]
+    fn scan_anodes() -> Result<(), MockedOutputPinError> {
+        let mut matrix = CharlieplexedMatrix::new(
+            [
+                MockedTriStatePin::default(),
+                MockedTriStatePin::default(),
+                MockedTriStatePin::default(),
+            ],
+            1,
+        );
+        matrix.set(0, 1, 0b1, 1, Repeat::Forever);
+        matrix.set(2, 0, 0b1, 1, Repeat::Forever);
+        for _ in 0..3 {
+            assert!(!matrix.update()?);
+        }
+        assert_eq!(b"HZZL", matrix.pins[0].levels());
+        assert_eq!(b"LHZZ", matrix.pins[1].levels());
+        assert_eq!(b"ZZZH", matrix.pins[2].levels());
+        Ok(())
+    }
+
+    #[test]
+    fn completed() -> Result<(), MockedOutputPinError> {
+        let mut matrix = CharlieplexedMatrix::new(
+            [MockedTriStatePin::default(), MockedTriStatePin::default()],
+            2,
+        );
+        matrix.set(1, 0, 0b1, 1, Repeat::Never);
+        assert!(!matrix.update()?);
+        assert!(matrix.update()?);
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_led_to_itself() {
+        let mut matrix = CharlieplexedMatrix::new(
+            [MockedTriStatePin::default(), MockedTriStatePin::default()],
+            1,
+        );
+        matrix.set(1, 1, 0b1, 1, Repeat::Never);
+    }
+
+    #[test]
+    #[should_panic(expected = "Must be at least 2 pins")]
+    fn no_pins() {
+        let _ = CharlieplexedMatrix::<MockedTriStatePin, 0>::new([], 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Must be at least 2 pins")]
+    fn single_pin() {
+        let _ = CharlieplexedMatrix::new([MockedTriStatePin::default()], 1);
+    }
+}
@@ -1,7 +1,13 @@
 //! Allow morse code output as sequence of states
+//!
+//! Bit literals are grouped by morse element (dit, dah, gap), not by nibble.
+#![allow(clippy::unusual_byte_groupings)]
+
+use crate::builder::MAX_NUMBER_OF_STATES;
 
 /// Morse code conversion errors
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum MorseError {
     UnsupportedCode,
     SequenceTooLong,
@@ -31,62 +37,114 @@ pub enum MorseError {
 /// assert_eq!(0b_00_01_01_01_00_0111_0111_0111_00_01_01_01_u128, sequence);
 /// assert_eq!(30_u16, len);
 /// ```
-pub fn str_to_morse(input: &str) -> Result<(u128, u16), MorseError> {
+pub const fn str_to_morse(input: &str) -> Result<(u128, u16), MorseError> {
+    let bytes = input.as_bytes();
     let mut sequence = 0b_0_u128;
     let mut len = 0_u16;
-    for c in input.chars() {
-        let (char_code, char_len) = char_to_morse(c)?;
-        if (len + char_len) > 127 {
+    let mut index = 0;
+    while index < bytes.len() {
+        let (char_code, char_len) = match char_to_morse(bytes[index]) {
+            Ok(code) => code,
+            Err(e) => return Err(e),
+        };
+        if (len + char_len) > MAX_NUMBER_OF_STATES {
             return Err(MorseError::SequenceTooLong);
         }
 
-        let mut char_code: u128 = char_code.into(); // convert to expected type
-        char_code = char_code << len;
+        sequence |= (char_code as u128) << len;
         len += char_len;
-        sequence = sequence | char_code;
+        index += 1;
     }
     Ok((sequence, len))
 }
 
-fn char_to_morse(morse_character: char) -> Result<(u32, u16), MorseError> {
+/// Human readable description of an error, usable in `const` context
+pub const fn describe(error: MorseError) -> &'static str {
+    match error {
+        MorseError::UnsupportedCode => "unsupported character in morse text",
+        MorseError::SequenceTooLong => "morse text exceeds 127 output states",
+    }
+}
+
+/// Encode a morse text at compile time
+///
+/// Expands to a constant `(u128, u16)` tuple of output states and number of
+/// output states, like [`str_to_morse`](morse/fn.str_to_morse.html) does at
+/// runtime. Unsupported characters, an empty or a too long text fail the
+/// build.
+///
+/// # Examples
+///
+/// ```rust
+/// use on_off_sequence_output::morse;
+///
+/// const SOS: (u128, u16) = morse!("SOS");
+/// assert_eq!(0b_00_01_01_01_00_0111_0111_0111_00_01_01_01_u128, SOS.0);
+/// assert_eq!(30_u16, SOS.1);
+/// ```
+///
+/// ```rust,compile_fail
+/// use on_off_sequence_output::morse;
+///
+/// let _ = morse!("sos");
+/// ```
+///
+/// ```rust,compile_fail
+/// use on_off_sequence_output::morse;
+///
+/// let _ = morse!("");
+/// ```
+#[macro_export]
+macro_rules! morse {
+    ($text:expr) => {{
+        const MORSE: (u128, u16) = match $crate::morse::str_to_morse($text) {
+            Ok((_, 0)) => panic!("morse text is empty"),
+            Ok(sequence) => sequence,
+            Err(error) => panic!("{}", $crate::morse::describe(error)),
+        };
+        MORSE
+    }};
+}
+
+const fn char_to_morse(morse_character: u8) -> Result<(u32, u16), MorseError> {
     match morse_character {
-        'A' => Ok((0b_00_0111_01, 8)),
-        'B' => Ok((0b_00_01_01_01_0111, 12)),
-        'C' => Ok((0b_00_01_0111_01_0111, 14)),
-        'D' => Ok((0b_00_01_01_0111, 10)),
-        'E' => Ok((0b_00_01, 4)),
-        'F' => Ok((0b_00_01_0111_01_01, 12)),
-        'G' => Ok((0b_00_01_0111_0111, 12)),
-        'H' => Ok((0b_00_01_01_01_01, 10)),
-        'I' => Ok((0b_00_01_01, 6)),
-        'J' => Ok((0b_00_0111_0111_0111_01, 16)),
-        'K' => Ok((0b_00_0111_01_0111, 12)),
-        'L' => Ok((0b_00_01_01_0111_01, 12)),
-        'M' => Ok((0b_00_0111_0111, 10)),
-        'N' => Ok((0b_00_01_0111, 8)),
-        'O' => Ok((0b_00_0111_0111_0111, 14)),
-        'P' => Ok((0b_00_01_0111_0111_01, 14)),
-        'Q' => Ok((0b_00_0111_01_0111_0111, 16)),
-        'R' => Ok((0b_00_01_0111_01, 10)),
-        'S' => Ok((0b_00_01_01_01, 8)),
-        'T' => Ok((0b_00_0111, 6)),
-        'U' => Ok((0b_00_0111_01_01, 10)),
-        'V' => Ok((0b_00_0111_01_01_01, 12)),
-        'W' => Ok((0b_00_0111_0111_01, 12)),
-        'X' => Ok((0b_00_0111_01_01_0111, 14)),
-        'Y' => Ok((0b_00_0111_0111_01_0111, 16)),
-        'Z' => Ok((0b_00_01_01_0111_0111, 14)),
-        ' ' => Ok((0b_00_00, 4)),
-        '1' => Ok((0b_00_0111_0111_0111_0111_01, 20)),
-        '2' => Ok((0b_00_0111_0111_0111_01_01, 18)),
-        '3' => Ok((0b_00_0111_0111_01_01_01, 16)),
-        '4' => Ok((0b_00_0111_01_01_01_01, 14)),
-        '5' => Ok((0b_00_01_01_01_01_01, 12)),
-        '6' => Ok((0b_00_01_01_01_01_0111, 14)),
-        '7' => Ok((0b_00_01_01_01_0111_0111, 16)),
-        '8' => Ok((0b_00_01_01_0111_0111_0111, 18)),
-        '9' => Ok((0b_00_01_0111_0111_0111_0111, 20)),
-        '0' => Ok((0b_00_0111_0111_0111_0111_0111, 22)),
+        b'A' => Ok((0b_00_0111_01, 8)),
+        b'B' => Ok((0b_00_01_01_01_0111, 12)),
+        b'C' => Ok((0b_00_01_0111_01_0111, 14)),
+        b'D' => Ok((0b_00_01_01_0111, 10)),
+        b'E' => Ok((0b_00_01, 4)),
+        b'F' => Ok((0b_00_01_0111_01_01, 12)),
+        b'G' => Ok((0b_00_01_0111_0111, 12)),
+        b'H' => Ok((0b_00_01_01_01_01, 10)),
+        b'I' => Ok((0b_00_01_01, 6)),
+        b'J' => Ok((0b_00_0111_0111_0111_01, 16)),
+        b'K' => Ok((0b_00_0111_01_0111, 12)),
+        b'L' => Ok((0b_00_01_01_0111_01, 12)),
+        b'M' => Ok((0b_00_0111_0111, 10)),
+        b'N' => Ok((0b_00_01_0111, 8)),
+        b'O' => Ok((0b_00_0111_0111_0111, 14)),
+        b'P' => Ok((0b_00_01_0111_0111_01, 14)),
+        b'Q' => Ok((0b_00_0111_01_0111_0111, 16)),
+        b'R' => Ok((0b_00_01_0111_01, 10)),
+        b'S' => Ok((0b_00_01_01_01, 8)),
+        b'T' => Ok((0b_00_0111, 6)),
+        b'U' => Ok((0b_00_0111_01_01, 10)),
+        b'V' => Ok((0b_00_0111_01_01_01, 12)),
+        b'W' => Ok((0b_00_0111_0111_01, 12)),
+        b'X' => Ok((0b_00_0111_01_01_0111, 14)),
+        b'Y' => Ok((0b_00_0111_0111_01_0111, 16)),
+        b'Z' => Ok((0b_00_01_01_0111_0111, 14)),
+        b' ' => Ok((0b_00_00, 4)),
+        b'1' => Ok((0b_00_0111_0111_0111_0111_01, 20)),
+        b'2' => Ok((0b_00_0111_0111_0111_01_01, 18)),
+        b'3' => Ok((0b_00_0111_0111_01_01_01, 16)),
+        b'4' => Ok((0b_00_0111_01_01_01_01, 14)),
+        b'5' => Ok((0b_00_01_01_01_01_01, 12)),
+        b'6' => Ok((0b_00_01_01_01_01_0111, 14)),
+        b'7' => Ok((0b_00_01_01_01_0111_0111, 16)),
+        b'8' => Ok((0b_00_01_01_0111_0111_0111, 18)),
+        b'9' => Ok((0b_00_01_0111_0111_0111_0111, 20)),
+        b'0' => Ok((0b_00_0111_0111_0111_0111_0111, 22)),
         _ => Err(MorseError::UnsupportedCode),
     }
 }
@@ -125,22 +125,31 @@ fn encode_90() {
 
 #[test]
 fn unsupported_error() {
-    match str_to_morse("sos") {
-        Ok(_) => assert!(false),
-        Err(err) => match err {
-            MorseError::UnsupportedCode => assert!(true),
-            MorseError::SequenceTooLong => assert!(false),
-        },
-    }
+    assert!(matches!(
+        str_to_morse("sos"),
+        Err(MorseError::UnsupportedCode)
+    ));
 }
 
 #[test]
 fn too_long_error() {
-    match str_to_morse("RUSTRUSTRUSTRUSTRUSTRUSTRUSTRUSTRUSTRUSTRUSTRUSTRUST") {
-        Ok(_) => assert!(false),
-        Err(err) => match err {
-            MorseError::UnsupportedCode => assert!(false),
-            MorseError::SequenceTooLong => assert!(true),
-        },
-    }
+    assert!(matches!(
+        str_to_morse("RUSTRUSTRUSTRUSTRUSTRUSTRUSTRUSTRUSTRUSTRUSTRUSTRUST"),
+        Err(MorseError::SequenceTooLong)
+    ));
+}
+
+#[test]
+fn non_ascii_unsupported_error() {
+    assert_eq!(Err(MorseError::UnsupportedCode), str_to_morse("SÖS"));
+}
+
+#[test]
+fn macro_is_const() {
+    const SOS: (u128, u16) = morse!("SOS");
+    assert_eq!(str_to_morse("SOS").unwrap(), SOS);
+    assert_eq!(
+        str_to_morse("RUST IS GOOD").unwrap(),
+        morse!("RUST IS GOOD")
+    );
 }
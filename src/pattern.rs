@@ -0,0 +1,268 @@
+//! Portable pattern value type
+//!
+//! A [`Pattern`] bundles everything an output needs to play a sequence:
+//! states, number of states, repetitions, timing and polarity. Patterns
+//! received from a host or stored in config flash are applied by
+//! `OnOffSequenceOutput::set_pattern()`.
+//!
+//! Patterns are exchanged in a compact, versioned binary encoding of at most
+//! [`MAX_ENCODED_SIZE`] bytes, see [`Pattern::encode()`]. With the optional
+//! `serde` feature, patterns can be serialized by any serde format as well,
+//! e.g. `postcard`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use on_off_sequence_output::pattern::*;
+//! use on_off_sequence_output::prelude::*;
+//!
+//! let pattern = Pattern::new(0b0101, 4, Repeat::Times(3))
+//!     .with_update_scale(250)
+//!     .with_polarity(Polarity::ActiveLow);
+//!
+//! let mut buffer = [0u8; MAX_ENCODED_SIZE];
+//! let encoded = pattern.encode(&mut buffer).unwrap();
+//! assert_eq!(&[1, 0b1101, 4, 3, 0, 250, 0, 0b0101], encoded);
+//! assert_eq!(Ok(pattern), Pattern::decode(encoded));
+//! ```
+//!
+//! # Encoding
+//!
+//! Version 1, all numbers little endian:
+//!
+//! | bytes | content                                                     |
+//! |-------|-------------------------------------------------------------|
+//! | 1     | version                                                     |
+//! | 1     | flags: bits 0-1 repeat (0 never, 1 times, 2 forever), bit 2 active low, bit 3 update scale present |
+//! | 1     | number of output states, 1 up to 127                        |
+//! | 0/2   | number of repetitions if repeated times                     |
+//! | 0/2   | update scale if present                                     |
+//! | 1-16  | output states, as many bytes as needed for the number of states |
+
+use crate::builder::{check_number_of_states, NumberOfStatesError};
+use crate::patterns::PatternError;
+use crate::Repeat;
+
+/// Version of the binary encoding written by `encode()`
+pub const ENCODING_VERSION: u8 = 1;
+
+/// Maximum size of an encoded pattern in bytes
+pub const MAX_ENCODED_SIZE: usize = 3 + 2 + 2 + 16;
+
+const REPEAT_NEVER: u8 = 0;
+const REPEAT_TIMES: u8 = 1;
+const REPEAT_FOREVER: u8 = 2;
+const REPEAT_MASK: u8 = 0b0011;
+const ACTIVE_LOW: u8 = 0b0100;
+const UPDATE_SCALE_PRESENT: u8 = 0b1000;
+
+/// Binary encoding and decoding errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CodecError {
+    /// The buffer can not take the encoded pattern
+    BufferTooSmall,
+    /// The encoding version is not supported
+    UnsupportedVersion,
+    /// The encoded pattern is truncated or has unknown flags
+    Malformed,
+    /// The number of output states is invalid
+    InvalidPattern,
+}
+
+/// Which pin level lights the LED
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Polarity {
+    /// On is driven high
+    ActiveHigh,
+    /// On is driven low, e.g. for an LED connected to the supply
+    ActiveLow,
+}
+
+/// A sequence of on/off states with its repetitions, timing and polarity
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pattern {
+    /// The output states, 1 equals on, see `OnOffSequenceOutput::set()`
+    pub output_states: u128,
+    /// How many bits of the output states are considered, 1 up to 127
+    pub number_of_output_states: u16,
+    /// How often is the pattern repeated
+    pub repeat: Repeat,
+    /// The update scale, or None to keep the one of the output
+    pub update_scale: Option<u16>,
+    /// Which pin level lights the LED
+    pub polarity: Polarity,
+}
+
+impl Pattern {
+    /// A pattern keeping the update scale of the output, active high
+    pub const fn new(output_states: u128, number_of_output_states: u16, repeat: Repeat) -> Self {
+        Self {
+            output_states,
+            number_of_output_states,
+            repeat,
+            update_scale: None,
+            polarity: Polarity::ActiveHigh,
+        }
+    }
+
+    /// The pattern with the given update scale
+    pub const fn with_update_scale(mut self, update_scale: u16) -> Self {
+        self.update_scale = Some(update_scale);
+        self
+    }
+
+    /// The pattern with the given polarity
+    pub const fn with_polarity(mut self, polarity: Polarity) -> Self {
+        self.polarity = polarity;
+        self
+    }
+
+    /// Check the number of output states
+    pub const fn validate(&self) -> Result<(), PatternError> {
+        match check_number_of_states(self.number_of_output_states) {
+            Ok(()) => Ok(()),
+            Err(NumberOfStatesError::Zero) => Err(PatternError::InvalidCount),
+            Err(NumberOfStatesError::TooMany) => Err(PatternError::SequenceTooLong),
+        }
+    }
+
+    /// The pin levels to output, 1 equals high
+    pub const fn levels(&self) -> u128 {
+        let mask = state_mask(self.number_of_output_states);
+        match self.polarity {
+            Polarity::ActiveHigh => self.output_states & mask,
+            Polarity::ActiveLow => !self.output_states & mask,
+        }
+    }
+
+    /// Encodes the pattern into the buffer
+    ///
+    /// Bits of the output states beyond the number of output states are not
+    /// encoded.
+    ///
+    /// # Returns
+    ///
+    /// The encoded part of the buffer
+    pub fn encode<'a>(&self, buffer: &'a mut [u8]) -> Result<&'a [u8], CodecError> {
+        self.validate().map_err(|_| CodecError::InvalidPattern)?;
+        let mut encoded = [0u8; MAX_ENCODED_SIZE];
+        let mut len = 3;
+        let mut flags = match self.repeat {
+            Repeat::Never => REPEAT_NEVER,
+            Repeat::Times(times) => {
+                encoded[len..len + 2].copy_from_slice(&times.to_le_bytes());
+                len += 2;
+                REPEAT_TIMES
+            }
+            Repeat::Forever => REPEAT_FOREVER,
+        };
+        if self.polarity == Polarity::ActiveLow {
+            flags |= ACTIVE_LOW;
+        }
+        if let Some(update_scale) = self.update_scale {
+            encoded[len..len + 2].copy_from_slice(&update_scale.to_le_bytes());
+            len += 2;
+            flags |= UPDATE_SCALE_PRESENT;
+        }
+        let mask = state_mask(self.number_of_output_states);
+        let state_bytes = state_bytes(self.number_of_output_states);
+        encoded[len..len + state_bytes]
+            .copy_from_slice(&(self.output_states & mask).to_le_bytes()[..state_bytes]);
+        len += state_bytes;
+        encoded[0] = ENCODING_VERSION;
+        encoded[1] = flags;
+        encoded[2] = self.number_of_output_states as u8;
+
+        let buffer = buffer.get_mut(..len).ok_or(CodecError::BufferTooSmall)?;
+        buffer.copy_from_slice(&encoded[..len]);
+        Ok(buffer)
+    }
+
+    /// Decodes a pattern encoded by `encode()`
+    ///
+    /// Bytes following the encoded pattern are ignored.
+    pub fn decode(bytes: &[u8]) -> Result<Self, CodecError> {
+        let mut reader = Reader { bytes };
+        if reader.take(1)?[0] != ENCODING_VERSION {
+            return Err(CodecError::UnsupportedVersion);
+        }
+        let flags = reader.take(1)?[0];
+        if flags & !(REPEAT_MASK | ACTIVE_LOW | UPDATE_SCALE_PRESENT) != 0 {
+            return Err(CodecError::Malformed);
+        }
+        let number_of_output_states = u16::from(reader.take(1)?[0]);
+        let repeat = match flags & REPEAT_MASK {
+            REPEAT_NEVER => Repeat::Never,
+            REPEAT_TIMES => Repeat::Times(reader.take_u16()?),
+            REPEAT_FOREVER => Repeat::Forever,
+            _ => return Err(CodecError::Malformed),
+        };
+        let polarity = if flags & ACTIVE_LOW != 0 {
+            Polarity::ActiveLow
+        } else {
+            Polarity::ActiveHigh
+        };
+        let update_scale = if flags & UPDATE_SCALE_PRESENT != 0 {
+            Some(reader.take_u16()?)
+        } else {
+            None
+        };
+        let mut pattern = Self {
+            output_states: 0,
+            number_of_output_states,
+            repeat,
+            update_scale,
+            polarity,
+        };
+        pattern.validate().map_err(|_| CodecError::InvalidPattern)?;
+
+        let mut states = [0u8; 16];
+        let state_bytes = state_bytes(number_of_output_states);
+        states[..state_bytes].copy_from_slice(reader.take(state_bytes)?);
+        let mask = state_mask(number_of_output_states);
+        pattern.output_states = u128::from_le_bytes(states) & mask;
+        Ok(pattern)
+    }
+}
+
+/// Mask of the considered bits of the output states
+const fn state_mask(number_of_output_states: u16) -> u128 {
+    if number_of_output_states >= 128 {
+        u128::MAX
+    } else {
+        (1u128 << number_of_output_states) - 1
+    }
+}
+
+/// Number of bytes encoding the output states
+// `div_ceil()` needs a recent toolchain
+#[allow(clippy::manual_div_ceil)]
+const fn state_bytes(number_of_output_states: u16) -> usize {
+    (number_of_output_states as usize + 7) / 8
+}
+
+/// Consumes the bytes of an encoded pattern
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CodecError> {
+        if self.bytes.len() < len {
+            return Err(CodecError::Malformed);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn take_u16(&mut self) -> Result<u16, CodecError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,127 @@
+/// Tests for the pattern value type and its encoding
+use super::*;
+
+fn round_trip(pattern: Pattern) -> Result<Pattern, CodecError> {
+    let mut buffer = [0u8; MAX_ENCODED_SIZE];
+    let encoded = pattern.encode(&mut buffer)?;
+    Pattern::decode(encoded)
+}
+
+#[test]
+fn levels() {
+    let pattern = Pattern::new(0b1111_0101, 4, Repeat::Never);
+    assert_eq!(0b0101, pattern.levels());
+    assert_eq!(0b1010, pattern.with_polarity(Polarity::ActiveLow).levels());
+    assert_eq!(
+        u128::MAX >> 1,
+        Pattern::new(u128::MAX, 127, Repeat::Never).levels()
+    );
+}
+
+#[test]
+fn encode_smallest() {
+    let mut buffer = [0u8; 4];
+    let pattern = Pattern::new(0b1, 1, Repeat::Never);
+    assert_eq!(Ok(&[1, 0, 1, 0b1][..]), pattern.encode(&mut buffer));
+}
+
+#[test]
+fn encode_forever() {
+    let mut buffer = [0u8; MAX_ENCODED_SIZE];
+    let pattern = Pattern::new(0x1_ff00, 17, Repeat::Forever);
+    assert_eq!(
+        Ok(&[1, 0b0010, 17, 0x00, 0xff, 0x01][..]),
+        pattern.encode(&mut buffer)
+    );
+}
+
+#[test]
+fn encode_ignores_bits_beyond_length() {
+    let mut buffer = [0u8; MAX_ENCODED_SIZE];
+    let pattern = Pattern::new(0b1110, 2, Repeat::Never);
+    assert_eq!(Ok(&[1, 0, 2, 0b10][..]), pattern.encode(&mut buffer));
+}
+
+#[test]
+fn encode_errors() {
+    let mut buffer = [0u8; 3];
+    assert_eq!(
+        Err(CodecError::BufferTooSmall),
+        Pattern::new(0b1, 1, Repeat::Never).encode(&mut buffer)
+    );
+    assert_eq!(
+        Err(CodecError::InvalidPattern),
+        Pattern::new(0b1, 0, Repeat::Never).encode(&mut buffer)
+    );
+}
+
+#[test]
+fn round_trips() {
+    let patterns = [
+        Pattern::new(0b1, 1, Repeat::Never),
+        Pattern::new(0b0101, 4, Repeat::Times(0)),
+        Pattern::new(0b0101, 4, Repeat::Times(u16::MAX)).with_update_scale(0),
+        Pattern::new(u128::MAX >> 1, 127, Repeat::Forever)
+            .with_update_scale(1000)
+            .with_polarity(Polarity::ActiveLow),
+    ];
+    for pattern in patterns.iter() {
+        assert_eq!(Ok(*pattern), round_trip(*pattern));
+    }
+}
+
+#[test]
+fn longest_encoding() {
+    let mut buffer = [0u8; MAX_ENCODED_SIZE];
+    let pattern = Pattern::new(1 << 126, 127, Repeat::Times(1)).with_update_scale(1);
+    assert_eq!(
+        Ok(MAX_ENCODED_SIZE),
+        pattern.encode(&mut buffer).map(|encoded| encoded.len())
+    );
+}
+
+#[test]
+fn decode_errors() {
+    assert_eq!(Err(CodecError::Malformed), Pattern::decode(&[]));
+    assert_eq!(
+        Err(CodecError::UnsupportedVersion),
+        Pattern::decode(&[2, 0, 1, 1])
+    );
+    assert_eq!(
+        Err(CodecError::Malformed),
+        Pattern::decode(&[1, 0b11, 1, 1])
+    );
+    assert_eq!(
+        Err(CodecError::Malformed),
+        Pattern::decode(&[1, 0b1_0000, 1, 1])
+    );
+    assert_eq!(Err(CodecError::Malformed), Pattern::decode(&[1, 0, 9, 1]));
+    assert_eq!(Err(CodecError::Malformed), Pattern::decode(&[1, 1, 1, 1]));
+    assert_eq!(
+        Err(CodecError::InvalidPattern),
+        Pattern::decode(&[1, 0, 0, 1])
+    );
+    assert_eq!(
+        Err(CodecError::InvalidPattern),
+        Pattern::decode(&[1, 0, 128, 1])
+    );
+}
+
+#[test]
+fn decode_ignores_trailing_bytes() {
+    assert_eq!(
+        Ok(Pattern::new(0b1, 1, Repeat::Never)),
+        Pattern::decode(&[1, 0, 1, 0xff, 0xff])
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    let pattern = Pattern::new(0b0101, 4, Repeat::Times(3))
+        .with_update_scale(250)
+        .with_polarity(Polarity::ActiveLow);
+    let mut buffer = [0u8; 64];
+    let serialized = postcard::to_slice(&pattern, &mut buffer).unwrap();
+    assert_eq!(Ok(pattern), postcard::from_bytes(serialized));
+}
@@ -0,0 +1,161 @@
+//! Standard indicator patterns
+//!
+//! Ready-to-use output states for `OnOffSequenceOutput::set()`. Each pattern
+//! is given as tuple of output states and number of output states; the
+//! resulting timing depends on the update scale of the output.
+//!
+//! # Example
+//!
+//! ```rust
+//! use on_off_sequence_output::patterns;
+//!
+//! let (states, len) = patterns::heartbeat();
+//! assert_eq!((0b_0000000101, 10), (states, len));
+//!
+//! // three blinks, then a pause
+//! let (states, len) = patterns::error_code(3).unwrap();
+//! assert_eq!((0b_000_01_01_01, 9), (states, len));
+//! ```
+
+use crate::builder::MAX_NUMBER_OF_STATES;
+
+/// Number of off states following the blinks of an error code; together with
+/// the off state of the last blink the pause lasts four states.
+const ERROR_CODE_PAUSE: u16 = 3;
+
+/// Pattern construction errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PatternError {
+    /// A count or duration is zero where at least one is needed
+    InvalidCount,
+    /// The pattern has more than 127 states
+    SequenceTooLong,
+}
+
+/// Two short pulses followed by a long pause
+///
+/// `#_#_______`
+pub const fn heartbeat() -> (u128, u16) {
+    crate::sequence!("#_#_______")
+}
+
+/// Two longer pulses followed by a long pause
+///
+/// `##__##________`
+pub const fn double_blink() -> (u128, u16) {
+    crate::sequence!("##__##________")
+}
+
+/// Symmetric blinking at the highest rate
+///
+/// `#_`
+pub const fn fast_error() -> (u128, u16) {
+    crate::sequence!("#_")
+}
+
+/// A short flash every twenty states
+///
+/// `#___________________`
+pub const fn slow_standby() -> (u128, u16) {
+    crate::sequence!("# off 19")
+}
+
+/// Morse code SOS followed by a word gap
+pub const fn sos() -> (u128, u16) {
+    crate::morse!("SOS  ")
+}
+
+/// On for some states, then off for some states
+///
+/// # Arguments
+///
+/// * `on` - Number of on states
+/// * `off` - Number of off states
+///
+/// # Returns
+///
+/// A result with Ok(tuple) of output states and number of output states,
+/// or Err(PatternError) if both counts are zero or the sum exceeds 127.
+///
+/// # Example
+///
+/// ```rust
+/// use on_off_sequence_output::patterns::duty_cycle;
+///
+/// assert_eq!(Ok((0b_0_111, 4)), duty_cycle(3, 1));
+/// ```
+pub const fn duty_cycle(on: u16, off: u16) -> Result<(u128, u16), PatternError> {
+    let len = on as u32 + off as u32;
+    if len == 0 {
+        return Err(PatternError::InvalidCount);
+    }
+    if len > MAX_NUMBER_OF_STATES as u32 {
+        return Err(PatternError::SequenceTooLong);
+    }
+    Ok((ones(on), len as u16))
+}
+
+/// Blink a number of times, then pause
+///
+/// # Arguments
+///
+/// * `count` - Number of blinks
+/// * `on` - Number of on states of a blink
+/// * `off` - Number of off states between two blinks
+/// * `pause` - Number of off states after the last blink, in addition to
+///   its off states
+///
+/// # Returns
+///
+/// A result with Ok(tuple) of output states and number of output states,
+/// or Err(PatternError) if `count` or `on` is zero or the pattern exceeds
+/// 127 states.
+pub const fn blinks(
+    count: u16,
+    on: u16,
+    off: u16,
+    pause: u16,
+) -> Result<(u128, u16), PatternError> {
+    if count == 0 || on == 0 {
+        return Err(PatternError::InvalidCount);
+    }
+    let blink_len = on as u32 + off as u32;
+    // u64 holds the length of any u16 arguments
+    let len = count as u64 * blink_len as u64 + pause as u64;
+    if len > MAX_NUMBER_OF_STATES as u64 {
+        return Err(PatternError::SequenceTooLong);
+    }
+    let blink = ones(on);
+    let mut sequence = 0_u128;
+    let mut index = 0;
+    while index < count as u32 {
+        sequence |= blink << (index * blink_len);
+        index += 1;
+    }
+    Ok((sequence, len as u16))
+}
+
+/// Error code: blink *n* times, then pause
+///
+/// `#_` per blink, followed by a pause of four states including the off
+/// state of the last blink.
+///
+/// # Arguments
+///
+/// * `n` - Error number, between 1 and 62
+pub const fn error_code(n: u16) -> Result<(u128, u16), PatternError> {
+    blinks(n, 1, 1, ERROR_CODE_PAUSE)
+}
+
+/// Output states with the lowest `n` bits set
+const fn ones(n: u16) -> u128 {
+    if n >= 128 {
+        !0
+    } else {
+        (1_u128 << n) - 1
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
+mod tests;
@@ -0,0 +1,66 @@
+/// Tests for the standard indicator patterns
+use super::*;
+use crate::dsl::parse;
+
+#[test]
+fn presets_match_notation() {
+    assert_eq!(parse("#_#_______"), Ok(heartbeat()));
+    assert_eq!(parse("##__##________"), Ok(double_blink()));
+    assert_eq!(parse("#_"), Ok(fast_error()));
+    assert_eq!(parse("#___________________"), Ok(slow_standby()));
+}
+
+#[test]
+fn sos_ends_with_word_gap() {
+    let (sequence, len) = sos();
+    assert_eq!(0b_00_01_01_01_00_0111_0111_0111_00_01_01_01_u128, sequence);
+    assert_eq!(38_u16, len);
+}
+
+#[test]
+fn duty_cycles() {
+    assert_eq!(Ok((0b_0_111_u128, 4_u16)), duty_cycle(3, 1));
+    assert_eq!(Ok((0b_111_u128, 3_u16)), duty_cycle(3, 0));
+    assert_eq!(Ok((0b_00_u128, 2_u16)), duty_cycle(0, 2));
+    assert_eq!(Ok((!0_u128 >> 1, 127_u16)), duty_cycle(127, 0));
+}
+
+#[test]
+fn duty_cycle_errors() {
+    assert_eq!(Err(PatternError::InvalidCount), duty_cycle(0, 0));
+    assert_eq!(Err(PatternError::SequenceTooLong), duty_cycle(100, 28));
+}
+
+#[test]
+fn error_codes() {
+    assert_eq!(Ok((0b_000_01_u128, 5_u16)), error_code(1));
+    assert_eq!(Ok((0b_000_01_01_01_u128, 9_u16)), error_code(3));
+    assert_eq!(Ok(127_u16), error_code(62).map(|(_, len)| len));
+}
+
+#[test]
+fn error_code_errors() {
+    assert_eq!(Err(PatternError::InvalidCount), error_code(0));
+    assert_eq!(Err(PatternError::SequenceTooLong), error_code(63));
+}
+
+#[test]
+fn blinks_with_long_on() {
+    assert_eq!(Ok((0b_00_0_111_0_111_u128, 10_u16)), blinks(2, 3, 1, 2));
+    assert_eq!(Ok((0b_1111_u128, 4_u16)), blinks(4, 1, 0, 0));
+}
+
+#[test]
+fn blinks_errors() {
+    assert_eq!(Err(PatternError::InvalidCount), blinks(0, 1, 1, 1));
+    assert_eq!(Err(PatternError::InvalidCount), blinks(1, 0, 1, 1));
+    assert_eq!(Err(PatternError::SequenceTooLong), blinks(1, 1, 1, 126));
+    assert_eq!(
+        Err(PatternError::SequenceTooLong),
+        blinks(65535, 65535, 65535, 0)
+    );
+    assert_eq!(
+        Err(PatternError::SequenceTooLong),
+        blinks(65535, 65535, 65535, 65535)
+    );
+}
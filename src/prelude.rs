@@ -1,8 +1,17 @@
 //! Import the needed types and traits to use the `update()` method.
 
+pub use crate::blink_code::BlinkCodeError;
+pub use crate::dsl::DslError;
 pub use crate::macros;
 pub use crate::morse::MorseError;
+pub use crate::number_of_states;
+pub use crate::pattern::Pattern;
+pub use crate::patterns::PatternError;
 pub use crate::position_of_highest_one;
+pub use crate::BitOrder;
+pub use crate::CatchUp;
+pub use crate::ErrorPolicy;
 pub use crate::OnOffSequenceOutput;
 pub use crate::OutputUpdate;
+pub use crate::PinWrites;
 pub use crate::Repeat;
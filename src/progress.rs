@@ -0,0 +1,199 @@
+//! Update scaling and repetition logic shared by all outputs
+
+use crate::builder::assert_number_of_states;
+use crate::Repeat;
+
+/// Progress of an output through its sequence of states
+///
+/// Tracks which state is output when, independent of what a state is and
+/// how it is output.
+#[derive(Clone, Copy)]
+pub(crate) struct Progress {
+    /// The update scaler: the clock rate at wich the output state changes
+    /// is equivalent the frequency of the update calls times *update_scale*
+    pub(crate) update_scale: u16,
+
+    /// The repeat configuration
+    pub(crate) repeat: Repeat,
+
+    /// How many states are in the sequence (min 1)
+    pub(crate) number_of_states: u16,
+
+    /// Internal state: Manage scaling
+    pub(crate) scale_index: u16,
+
+    /// internal state: Manage next output state
+    pub(crate) state_index: u16,
+
+    /// Internal state: Run output indicator
+    ///
+    /// # Values
+    ///
+    /// * true - either a run is not completed or there are more repetitions to do
+    /// * false - run is completed (intermediate) and no more repetitions are
+    ///   needed.
+    pub(crate) run_output: bool,
+}
+
+impl Progress {
+    pub(crate) const fn new(update_scale: u16, number_of_states: u16, repeat: Repeat) -> Self {
+        Self {
+            update_scale,
+            repeat,
+            number_of_states,
+            scale_index: 0u16,
+            state_index: 0u16,
+            run_output: true,
+        }
+    }
+
+    /// Start over with a new sequence
+    pub(crate) fn restart(&mut self, number_of_states: u16, repeat: Repeat) {
+        self.number_of_states = number_of_states;
+        self.repeat = repeat;
+        self.scale_index = 0u16;
+        self.state_index = 0u16;
+        self.run_output = true;
+    }
+
+    /// Handle the update scale for one update period
+    ///
+    /// # Returns
+    ///
+    /// true if the next state is due
+    pub(crate) fn tick(&mut self) -> bool {
+        self.scale_index += 1;
+        if self.update_scale > self.scale_index {
+            return false;
+        }
+        self.scale_index = 0;
+        true
+    }
+
+    /// Make the next update period due, e.g. to output a state again
+    pub(crate) fn retry_next_tick(&mut self) {
+        self.scale_index = self.update_scale.saturating_sub(1);
+    }
+
+    /// Handle the update scale for a number of update periods
+    ///
+    /// # Returns
+    ///
+    /// the number of due states
+    pub(crate) fn ticks(&mut self, ticks: u16) -> u32 {
+        let update_scale = u32::from(self.update_scale.max(1));
+        let elapsed = u32::from(self.scale_index) + u32::from(ticks);
+        self.scale_index = (elapsed % update_scale) as u16;
+        elapsed / update_scale
+    }
+
+    /// The state to output next, or None if the output is completed
+    pub(crate) fn current(&self) -> Option<u16> {
+        if self.run_output {
+            Some(self.state_index)
+        } else {
+            None
+        }
+    }
+
+    /// Advance to the next state, handles the repetitions
+    pub(crate) fn advance(&mut self) {
+        // handle the output sequence
+        let was_running = self.run_output;
+        if self.run_output {
+            self.state_index += 1;
+            if self.state_index >= self.number_of_states {
+                // all states are "printed"
+                self.run_output = false;
+                self.state_index = 0;
+            }
+        }
+
+        // handle the repetitions
+        if !self.run_output {
+            self.repeat = match self.repeat {
+                Repeat::Never => Repeat::Never,
+                Repeat::Forever => Repeat::Forever,
+                Repeat::Times(n) => {
+                    if n > 0 {
+                        Repeat::Times(n - 1)
+                    } else {
+                        Repeat::Never
+                    }
+                }
+            };
+            self.run_output = match self.repeat {
+                Repeat::Never => false,
+                Repeat::Forever => true,
+                Repeat::Times(_) => true,
+            };
+            if was_running {
+                event!(
+                    debug,
+                    "repetition boundary, {:?}, completed: {}",
+                    self.repeat,
+                    !self.run_output
+                );
+            }
+        }
+    }
+
+    /// Check if no further update is necessary to complete the output
+    pub(crate) fn is_completed(&self) -> bool {
+        !self.run_output
+    }
+
+    /// Check if the current state lasted its full duration, i.e. the next
+    /// update period starts a new state
+    pub(crate) fn at_state_boundary(&self) -> bool {
+        self.scale_index == 0 || !self.run_output
+    }
+
+    /// Check if the current run of the states is completed, i.e. the next
+    /// update period starts a new repetition
+    pub(crate) fn at_repetition_boundary(&self) -> bool {
+        (self.scale_index == 0 && self.state_index == 0) || !self.run_output
+    }
+}
+
+/// On/off sequence of a single LED, e.g. in a matrix
+#[derive(Clone, Copy)]
+pub(crate) struct LedSequence {
+    /// Output states, see `OnOffSequenceOutput`
+    pub(crate) output_states: u128,
+    /// Scaling, position and repetitions of the states
+    pub(crate) progress: Progress,
+    /// Whether the LED is lit, e.g. when its row is selected
+    pub(crate) lit: bool,
+}
+
+impl LedSequence {
+    pub(crate) const fn off(update_scale: u16) -> Self {
+        Self {
+            output_states: 0,
+            progress: Progress::new(update_scale, 1, Repeat::Never),
+            lit: false,
+        }
+    }
+
+    pub(crate) fn set(
+        &mut self,
+        output_states: u128,
+        number_of_output_states: u16,
+        repeat: Repeat,
+    ) {
+        assert_number_of_states(number_of_output_states);
+        self.output_states = output_states;
+        self.progress.restart(number_of_output_states, repeat);
+    }
+
+    /// Progresses the sequence by one update period
+    pub(crate) fn tick(&mut self) {
+        if self.progress.tick() {
+            if let Some(state_index) = self.progress.current() {
+                self.lit = (self.output_states >> state_index) & 1 == 1;
+            }
+            self.progress.advance();
+        }
+    }
+}
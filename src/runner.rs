@@ -0,0 +1,259 @@
+//! Thread based runner for Linux and other `std` targets
+//!
+//! On a Linux gateway there is no timer interrupt calling `update()`. A
+//! [`Runner`] moves an `OnOffSequenceOutput` into a thread, which updates it
+//! at a fixed tick scheduled by `std::time::Instant`. Ticks missed by a late
+//! wake-up are caught up by `update_by()`. New patterns are sent to the
+//! thread as [`Pattern`] values.
+//!
+//! Any `OutputPin` can be driven, e.g. a `linux_embedded_hal::CdevPin`, see
+//! [`cdev_output_pin()`] with the `linux` feature. Without GPIO hardware, a
+//! [`MemoryPin`] records the levels in memory and a [`FilePin`] writes them
+//! to a file like the sysfs value file.
+//!
+//! Only available with the `std` feature.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use on_off_sequence_output::pattern::Pattern;
+//! use on_off_sequence_output::prelude::*;
+//! use on_off_sequence_output::runner::{cdev_output_pin, Runner};
+//! use std::time::Duration;
+//!
+//! let pin = cdev_output_pin("/dev/gpiochip0", 17, "status-led")?;
+//! let runner = Runner::spawn(OnOffSequenceOutput::new(pin, 1), Duration::from_millis(250));
+//! runner.set_pattern(Pattern::new(0b0101, 4, Repeat::Forever))?;
+//! // ...
+//! let output = runner.stop()?;
+//! ```
+
+use crate::pattern::Pattern;
+use crate::{OnOffSequenceOutput, OutputUpdate};
+use core::convert::Infallible;
+use embedded_hal::digital::v2::OutputPin;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+
+/// Runner errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RunnerError {
+    /// The number of output states of the pattern is invalid
+    InvalidPattern,
+    /// The thread has ended, e.g. on a pin error
+    Stopped,
+}
+
+/// Message to the thread
+enum Message {
+    Pattern(Pattern),
+    Stop,
+}
+
+/// Output updated by a thread
+pub struct Runner<T: OutputPin> {
+    messages: Sender<Message>,
+    thread: JoinHandle<Result<OnOffSequenceOutput<T>, T::Error>>,
+}
+
+impl<T> Runner<T>
+where
+    T: OutputPin + Send + 'static,
+    T::Error: Send + 'static,
+{
+    /// Moves the output into a new thread which updates it every tick
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The output, plays its current pattern right away
+    /// * `tick` - The period of the updates, the update scale applies
+    ///
+    /// # Panics
+    ///
+    /// If the tick is zero
+    pub fn spawn(output: OnOffSequenceOutput<T>, tick: Duration) -> Self {
+        if tick.is_zero() {
+            panic!("Zero tick does not make sense");
+        };
+        let (messages, receiver) = mpsc::channel();
+        let thread = thread::spawn(move || {
+            let mut output = output;
+            let mut next_tick = Instant::now() + tick;
+            loop {
+                let timeout = next_tick.saturating_duration_since(Instant::now());
+                match receiver.recv_timeout(timeout) {
+                    Ok(Message::Pattern(pattern)) => {
+                        // validated by `set_pattern()`
+                        let _ = output.set_pattern(&pattern);
+                        continue;
+                    }
+                    Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => {
+                        return Ok(output);
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                }
+                let late = Instant::now().saturating_duration_since(next_tick);
+                let ticks = 1 + late.as_nanos() / tick.as_nanos();
+                let ticks = ticks.min(u128::from(u16::MAX)) as u16;
+                next_tick += tick * u32::from(ticks);
+                output.update_by(ticks)?;
+            }
+        });
+        Self { messages, thread }
+    }
+
+    /// Sends a new pattern to the thread
+    pub fn set_pattern(&self, pattern: Pattern) -> Result<(), RunnerError> {
+        pattern
+            .validate()
+            .map_err(|_| RunnerError::InvalidPattern)?;
+        self.messages
+            .send(Message::Pattern(pattern))
+            .map_err(|_| RunnerError::Stopped)
+    }
+
+    /// Check if the thread has ended, e.g. on a pin error
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Stops the thread
+    ///
+    /// # Returns
+    ///
+    /// * Ok(output) - the output, to be reused
+    /// * Err(pin error) - if the thread ended on a pin error
+    pub fn stop(self) -> Result<OnOffSequenceOutput<T>, T::Error> {
+        // fails if the thread has ended already
+        let _ = self.messages.send(Message::Stop);
+        match self.thread.join() {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
+
+/// Requests a GPIO line of a Linux GPIO character device as output pin
+///
+/// # Arguments
+///
+/// * `chip` - Path of the GPIO chip, e.g. `/dev/gpiochip0`
+/// * `line` - Offset of the line at the chip
+/// * `consumer` - Label of the user of the line
+///
+/// Only available with the `linux` feature.
+#[cfg(feature = "linux")]
+pub fn cdev_output_pin<P: AsRef<Path>>(
+    chip: P,
+    line: u32,
+    consumer: &str,
+) -> Result<linux_embedded_hal::CdevPin, linux_embedded_hal::gpio_cdev::Error> {
+    use linux_embedded_hal::gpio_cdev::{Chip, LineRequestFlags};
+
+    let handle = Chip::new(chip)?
+        .get_line(line)?
+        .request(LineRequestFlags::OUTPUT, 0, consumer)?;
+    linux_embedded_hal::CdevPin::new(handle)
+}
+
+/// Fake pin recording the driven levels in memory
+///
+/// Clones share the recorded levels, so a clone can be inspected while the
+/// pin is owned by a runner.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryPin {
+    levels: Arc<Mutex<Vec<(Instant, bool)>>>,
+}
+
+impl MemoryPin {
+    /// Creates a pin without recorded levels
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The levels driven so far with the instant they were driven
+    pub fn history(&self) -> Vec<(Instant, bool)> {
+        self.lock().clone()
+    }
+
+    /// The levels driven so far, true is high
+    pub fn levels(&self) -> Vec<bool> {
+        self.lock().iter().map(|&(_, high)| high).collect()
+    }
+
+    /// The level driven last, None if none was driven
+    pub fn level(&self) -> Option<bool> {
+        self.lock().last().map(|&(_, high)| high)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(Instant, bool)>> {
+        // the levels stay consistent even if a holder panicked
+        self.levels
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn record(&mut self, high: bool) {
+        self.lock().push((Instant::now(), high));
+    }
+}
+
+impl OutputPin for MemoryPin {
+    type Error = Infallible;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.record(true);
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.record(false);
+        Ok(())
+    }
+}
+
+/// Fake pin writing the driven level to a file
+///
+/// Like the sysfs value file of a GPIO, the file contains `1` if the pin is
+/// high and `0` if low.
+#[derive(Debug)]
+pub struct FilePin {
+    path: PathBuf,
+}
+
+impl FilePin {
+    /// Creates the file, the pin is low
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut pin = Self {
+            path: path.as_ref().to_path_buf(),
+        };
+        pin.set_low()?;
+        Ok(pin)
+    }
+
+    /// The path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl OutputPin for FilePin {
+    type Error = io::Error;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        fs::write(&self.path, "1\n")
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        fs::write(&self.path, "0\n")
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,129 @@
+/// Tests for the thread based runner and the fake pins
+use super::*;
+use crate::Repeat;
+
+const TICK: Duration = Duration::from_millis(2);
+
+/// Generous upper bound for the runner thread to get somewhere, so loaded
+/// machines do not fail the tests
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Polls the condition until it holds or the timeout elapsed
+fn wait_until<F: FnMut() -> bool>(mut condition: F) -> bool {
+    let deadline = Instant::now() + TIMEOUT;
+    while !condition() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(TICK);
+    }
+    true
+}
+
+/// Removes repeatedly driven levels
+fn edges(levels: Vec<bool>) -> Vec<bool> {
+    let mut edges = levels;
+    edges.dedup();
+    edges
+}
+
+#[test]
+fn plays_the_output() {
+    let pin = MemoryPin::new();
+    let mut output = OnOffSequenceOutput::new(pin.clone(), 1);
+    output.set(0b0101, 4, Repeat::Never);
+    let runner = Runner::spawn(output, TICK);
+    assert!(wait_until(|| pin.levels().len() >= 4));
+    assert!(!runner.is_finished());
+    runner.stop().unwrap();
+    assert_eq!(std::vec![true, false, true, false], edges(pin.levels()));
+}
+
+#[test]
+fn set_pattern() {
+    let pin = MemoryPin::new();
+    let mut output = OnOffSequenceOutput::new(pin.clone(), 1);
+    output.set(0b0, 1, Repeat::Never);
+    let runner = Runner::spawn(output, TICK);
+    runner
+        .set_pattern(Pattern::new(0b1, 1, Repeat::Never).with_update_scale(3))
+        .unwrap();
+    assert!(wait_until(|| pin.level() == Some(true)));
+    runner.stop().unwrap();
+}
+
+#[test]
+fn update_scale_applies() {
+    let pin = MemoryPin::new();
+    let mut output = OnOffSequenceOutput::new(pin.clone(), 5);
+    output.set(0b1, 1, Repeat::Never);
+    let start = Instant::now();
+    let runner = Runner::spawn(output, TICK);
+    assert!(wait_until(|| !pin.history().is_empty()));
+    runner.stop().unwrap();
+    let history = pin.history();
+    assert_eq!(1, history.len());
+    assert!(history[0].0 - start >= TICK * 5);
+}
+
+#[test]
+fn invalid_pattern() {
+    let runner = Runner::spawn(OnOffSequenceOutput::new(MemoryPin::new(), 1), TICK);
+    assert_eq!(
+        Err(RunnerError::InvalidPattern),
+        runner.set_pattern(Pattern::new(0, 0, Repeat::Never))
+    );
+    runner.stop().unwrap();
+}
+
+/// Fails at the first level driven
+struct BrokenPin;
+
+impl OutputPin for BrokenPin {
+    type Error = ();
+
+    fn set_high(&mut self) -> Result<(), ()> {
+        Err(())
+    }
+
+    fn set_low(&mut self) -> Result<(), ()> {
+        Err(())
+    }
+}
+
+#[test]
+fn pin_error_ends_the_thread() {
+    let runner = Runner::spawn(OnOffSequenceOutput::new(BrokenPin, 1), TICK);
+    assert!(wait_until(|| runner.is_finished()));
+    assert_eq!(
+        Err(RunnerError::Stopped),
+        runner.set_pattern(Pattern::new(0b1, 1, Repeat::Never))
+    );
+    assert!(runner.stop().is_err());
+}
+
+#[test]
+fn memory_pin_clones_share_levels() {
+    let mut pin = MemoryPin::new();
+    let probe = pin.clone();
+    assert_eq!(None, probe.level());
+    pin.set_high().unwrap();
+    pin.set_low().unwrap();
+    assert_eq!(std::vec![true, false], probe.levels());
+    assert_eq!(Some(false), probe.level());
+}
+
+#[test]
+fn file_pin() {
+    let path = std::env::temp_dir().join(std::format!(
+        "on-off-sequence-output-file-pin-{}",
+        std::process::id()
+    ));
+    let mut pin = FilePin::new(&path).unwrap();
+    assert_eq!("0\n", fs::read_to_string(pin.path()).unwrap());
+    pin.set_high().unwrap();
+    assert_eq!("1\n", fs::read_to_string(&path).unwrap());
+    pin.set_low().unwrap();
+    assert_eq!("0\n", fs::read_to_string(&path).unwrap());
+    fs::remove_file(&path).unwrap();
+}
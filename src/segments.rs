@@ -0,0 +1,350 @@
+//! Animations on seven-segment displays and LED bars
+//!
+//! A [`SegmentOutput`] drives up to 16 segments, one pin each, and plays a
+//! sequence of [`Frames`]: every frame is a bitmask of the lit segments, the
+//! first segment in the lowest bit. Frames change with the scaling and
+//! repetitions of `OnOffSequenceOutput`.
+//!
+//! Animations for LED bars are built in (chase, bounce, fill), for
+//! seven-segment displays text and numbers are shown frame by frame, one
+//! character per frame. Segments of a seven-segment display are in the
+//! order `a` to `g` followed by the decimal point.
+//!
+//! # Example
+//!
+//! ```rust
+//! use on_off_sequence_output::segments::Frames;
+//!
+//! let fill = Frames::fill(3);
+//! assert_eq!(&[0b000, 0b001, 0b011, 0b111], fill.as_slice());
+//!
+//! let text = Frames::text("E1.").unwrap();
+//! assert_eq!(&[0b0111_1001, 0b1000_0110], text.as_slice());
+//! ```
+//!
+//! ```rust,ignore
+//! use on_off_sequence_output::prelude::*;
+//! use on_off_sequence_output::segments::{Frames, SegmentOutput};
+//!
+//! let mut bar = SegmentOutput::new([led0, led1, led2, led3, led4], UPDATE_SCALE);
+//! bar.set(Frames::bounce(5), Repeat::Forever);
+//! loop {
+//!    bar.update().unwrap();
+//!    wait(1.ms());
+//! }
+//! ```
+
+use crate::progress::Progress;
+use crate::{OutputUpdate, Repeat};
+use embedded_hal::digital::v2::OutputPin;
+
+/// Maximum number of segments driven
+pub const MAX_NUMBER_OF_SEGMENTS: usize = 16;
+
+/// Maximum number of frames in a sequence
+pub const MAX_NUMBER_OF_FRAMES: usize = 32;
+
+/// Decimal point of a seven-segment display
+pub const DECIMAL_POINT: u16 = 0b1000_0000;
+
+/// Frame construction errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SegmentError {
+    /// A character can not be shown on a seven-segment display
+    UnsupportedCharacter,
+    /// More than 32 frames
+    SequenceTooLong,
+    /// Not a single frame
+    Empty,
+}
+
+/// The segments `a` to `g` lit to show a character
+///
+/// # Returns
+///
+/// * Some(segments) - for digits, blank, `-`, `_` and the letters which are
+///   readable on seven segments, regardless of case
+/// * None - otherwise
+pub const fn seven_segment_glyph(character: u8) -> Option<u16> {
+    let segments = match character.to_ascii_uppercase() {
+        b'0' | b'O' => 0b011_1111,
+        b'1' | b'I' => 0b000_0110,
+        b'2' | b'Z' => 0b101_1011,
+        b'3' => 0b100_1111,
+        b'4' => 0b110_0110,
+        b'5' | b'S' => 0b110_1101,
+        b'6' => 0b111_1101,
+        b'7' => 0b000_0111,
+        b'8' => 0b111_1111,
+        b'9' => 0b110_1111,
+        b'A' => 0b111_0111,
+        b'B' => 0b111_1100,
+        b'C' => 0b011_1001,
+        b'D' => 0b101_1110,
+        b'E' => 0b111_1001,
+        b'F' => 0b111_0001,
+        b'G' => 0b011_1101,
+        b'H' => 0b111_0110,
+        b'J' => 0b001_1110,
+        b'L' => 0b011_1000,
+        b'N' => 0b101_0100,
+        b'P' => 0b111_0011,
+        b'R' => 0b101_0000,
+        b'T' => 0b111_1000,
+        b'U' => 0b011_1110,
+        b'Y' => 0b110_1110,
+        b' ' => 0b000_0000,
+        b'-' => 0b100_0000,
+        b'_' => 0b000_1000,
+        _ => return None,
+    };
+    Some(segments)
+}
+
+/// A sequence of frames, each a bitmask of the lit segments
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frames {
+    frames: [u16; MAX_NUMBER_OF_FRAMES],
+    len: usize,
+}
+
+impl Frames {
+    const fn empty() -> Self {
+        Self {
+            frames: [0; MAX_NUMBER_OF_FRAMES],
+            len: 0,
+        }
+    }
+
+    const fn off() -> Self {
+        let mut result = Self::empty();
+        result.len = 1;
+        result
+    }
+
+    /// Frames from a slice
+    pub const fn new(frames: &[u16]) -> Result<Self, SegmentError> {
+        if frames.is_empty() {
+            return Err(SegmentError::Empty);
+        }
+        if frames.len() > MAX_NUMBER_OF_FRAMES {
+            return Err(SegmentError::SequenceTooLong);
+        }
+        let mut result = Self::empty();
+        while result.len < frames.len() {
+            result.frames[result.len] = frames[result.len];
+            result.len += 1;
+        }
+        Ok(result)
+    }
+
+    /// A single lit segment running from the first to the last segment
+    ///
+    /// # Panics
+    ///
+    /// If `segments` is zero or more than 16
+    pub const fn chase(segments: usize) -> Self {
+        check_segments(segments);
+        let mut result = Self::empty();
+        while result.len < segments {
+            result.frames[result.len] = 1 << result.len;
+            result.len += 1;
+        }
+        result
+    }
+
+    /// A single lit segment running from the first to the last segment and
+    /// back, without repeating the turning points
+    ///
+    /// # Panics
+    ///
+    /// If `segments` is zero or more than 16
+    pub const fn bounce(segments: usize) -> Self {
+        let mut result = Self::chase(segments);
+        let mut segment = segments - 1;
+        while segment > 1 {
+            segment -= 1;
+            result.frames[result.len] = 1 << segment;
+            result.len += 1;
+        }
+        result
+    }
+
+    /// A bar filling up from no segment to all segments lit, e.g. to show
+    /// progress
+    ///
+    /// # Panics
+    ///
+    /// If `segments` is zero or more than 16
+    pub const fn fill(segments: usize) -> Self {
+        check_segments(segments);
+        let mut result = Self::empty();
+        while result.len <= segments {
+            result.frames[result.len] = ((1u32 << result.len) - 1) as u16;
+            result.len += 1;
+        }
+        result
+    }
+
+    /// A frame alternating with all segments off, e.g. a blinking digit
+    pub const fn blink(frame: u16) -> Self {
+        let mut result = Self::empty();
+        result.frames[0] = frame;
+        result.len = 2;
+        result
+    }
+
+    /// Text on a seven-segment display, one character per frame
+    ///
+    /// A `.` lights the decimal point of the previous character, or is a
+    /// frame of its own if there is none.
+    pub const fn text(text: &str) -> Result<Self, SegmentError> {
+        let text = text.as_bytes();
+        let mut result = Self::empty();
+        let mut index = 0;
+        while index < text.len() {
+            let character = text[index];
+            index += 1;
+            if character == b'.'
+                && result.len > 0
+                && result.frames[result.len - 1] & DECIMAL_POINT == 0
+            {
+                result.frames[result.len - 1] |= DECIMAL_POINT;
+                continue;
+            }
+            let frame = if character == b'.' {
+                DECIMAL_POINT
+            } else {
+                match seven_segment_glyph(character) {
+                    Some(frame) => frame,
+                    None => return Err(SegmentError::UnsupportedCharacter),
+                }
+            };
+            if result.len == MAX_NUMBER_OF_FRAMES {
+                return Err(SegmentError::SequenceTooLong);
+            }
+            result.frames[result.len] = frame;
+            result.len += 1;
+        }
+        if result.len == 0 {
+            return Err(SegmentError::Empty);
+        }
+        Ok(result)
+    }
+
+    /// A decimal number on a seven-segment display, one digit per frame
+    pub const fn number(number: u32) -> Self {
+        let mut digits = [0u16; 10];
+        let mut number_of_digits = 0;
+        let mut rest = number;
+        loop {
+            digits[number_of_digits] = match seven_segment_glyph(b'0' + (rest % 10) as u8) {
+                Some(frame) => frame,
+                None => 0,
+            };
+            number_of_digits += 1;
+            rest /= 10;
+            if rest == 0 {
+                break;
+            }
+        }
+        let mut result = Self::empty();
+        while result.len < number_of_digits {
+            result.frames[result.len] = digits[number_of_digits - 1 - result.len];
+            result.len += 1;
+        }
+        result
+    }
+
+    /// The frames in the order they are shown
+    pub fn as_slice(&self) -> &[u16] {
+        &self.frames[..self.len]
+    }
+}
+
+const fn check_segments(segments: usize) {
+    if segments == 0 || segments > MAX_NUMBER_OF_SEGMENTS {
+        panic!("Must be 1 up to 16 segments");
+    }
+}
+
+/// Output of frames on segments, one pin each
+pub struct SegmentOutput<P: OutputPin, const N: usize> {
+    /// The segment pins, high lights a segment
+    pub pins: [P; N],
+
+    /// The frames shown
+    frames: Frames,
+
+    /// Scaling, position and repetitions of the frames
+    progress: Progress,
+}
+
+impl<P: OutputPin, const N: usize> SegmentOutput<P, N> {
+    /// Initializes a new segment output
+    ///
+    /// # Arguments
+    ///
+    /// * `pins` - As output initialized GPIO pins, first segment first
+    /// * `update_scale` - Scale factor:
+    ///   frame change frequency = update frequency * update_scale
+    ///
+    /// # Notes
+    ///
+    /// * Default is all segments off
+    ///
+    /// # Panics
+    ///
+    /// If there are no pins or more than 16
+    pub fn new(pins: [P; N], update_scale: u16) -> Self {
+        check_segments(N);
+        Self {
+            pins,
+            frames: Frames::off(),
+            progress: Progress::new(update_scale, 1, Repeat::Never),
+        }
+    }
+
+    /// Set new frames
+    ///
+    /// # Arguments
+    ///
+    /// * `frames` - Frames in the order they are shown
+    /// * `repeat` - How often are the frames repeated
+    ///
+    /// # Panics
+    ///
+    /// If there are no frames
+    pub fn set(&mut self, frames: Frames, repeat: Repeat) {
+        if frames.len == 0 {
+            panic!("Zero frames do not make sense");
+        };
+        self.progress.restart(frames.len as u16, repeat);
+        self.frames = frames;
+    }
+}
+
+impl<P: OutputPin, const N: usize> OutputUpdate for SegmentOutput<P, N> {
+    type Error = P::Error;
+
+    /// Updates the output logic, potentially shows the next frame
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        if self.progress.tick() {
+            if let Some(state_index) = self.progress.current() {
+                let frame = self.frames.frames[state_index as usize];
+                for (segment, pin) in self.pins.iter_mut().enumerate() {
+                    if frame & (1 << segment) != 0 {
+                        pin.set_high()?;
+                    } else {
+                        pin.set_low()?;
+                    }
+                }
+            }
+            self.progress.advance();
+        }
+        Ok(self.progress.is_completed())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,132 @@
+/// Tests for the segment animations
+use super::*;
+use crate::tests::mocks::*;
+
+mod frames {
+    use super::*;
+
+    #[test]
+    fn chase() {
+        assert_eq!(&[0b001, 0b010, 0b100], Frames::chase(3).as_slice());
+        assert_eq!(&[0b1], Frames::chase(1).as_slice());
+    }
+
+    #[test]
+    fn bounce() {
+        assert_eq!(
+            &[0b0001, 0b0010, 0b0100, 0b1000, 0b0100, 0b0010],
+            Frames::bounce(4).as_slice()
+        );
+        assert_eq!(&[0b01, 0b10], Frames::bounce(2).as_slice());
+        assert_eq!(30, Frames::bounce(16).as_slice().len());
+    }
+
+    #[test]
+    fn fill() {
+        assert_eq!(&[0b00, 0b01, 0b11], Frames::fill(2).as_slice());
+        assert_eq!(Some(&0xffff), Frames::fill(16).as_slice().last());
+    }
+
+    #[test]
+    fn blink() {
+        let eight = seven_segment_glyph(b'8').unwrap();
+        assert_eq!(&[eight, 0], Frames::blink(eight).as_slice());
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(
+            Ok(&[0b111_0110, 0b111_1001, 0b011_1000, 0b111_0011][..]),
+            Frames::text("HELP").as_ref().map(Frames::as_slice)
+        );
+        assert_eq!(
+            Frames::text("HELP"),
+            Frames::text("help"),
+            "case is ignored"
+        );
+    }
+
+    #[test]
+    fn text_with_decimal_points() {
+        assert_eq!(
+            Ok(&[DECIMAL_POINT, 0b000_0110 | DECIMAL_POINT, DECIMAL_POINT][..]),
+            Frames::text(".1..").as_ref().map(Frames::as_slice)
+        );
+    }
+
+    #[test]
+    fn text_errors() {
+        assert_eq!(Err(SegmentError::UnsupportedCharacter), Frames::text("XY"));
+        assert_eq!(
+            Err(SegmentError::SequenceTooLong),
+            Frames::text("0123456789012345678901234567890123")
+        );
+        assert!(Frames::text("01234567890123456789012345678901").is_ok());
+        assert_eq!(Err(SegmentError::Empty), Frames::text(""));
+    }
+
+    #[test]
+    fn number() {
+        assert_eq!(Frames::text("0"), Ok(Frames::number(0)));
+        assert_eq!(Frames::text("4711"), Ok(Frames::number(4711)));
+        assert_eq!(Frames::text("4294967295"), Ok(Frames::number(u32::MAX)));
+    }
+
+    #[test]
+    fn from_slice() {
+        assert_eq!(&[0b1, 0b0], Frames::new(&[0b1, 0b0]).unwrap().as_slice());
+        assert_eq!(
+            Err(SegmentError::SequenceTooLong),
+            Frames::new(&[0; MAX_NUMBER_OF_FRAMES + 1])
+        );
+        assert_eq!(Err(SegmentError::Empty), Frames::new(&[]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_segments() {
+        Frames::chase(17);
+    }
+}
+
+mod segment_output {
+    use super::*;
+
+    #[test]
+    fn chase() -> Result<(), MockedOutputPinError> {
+        let pins = [
+            MockedOutputPin::expected(3, 0b001_u128),
+            MockedOutputPin::expected(3, 0b010_u128),
+            MockedOutputPin::expected(3, 0b100_u128),
+        ];
+        let mut bar = SegmentOutput::new(pins, 1);
+        bar.set(Frames::chase(3), Repeat::Never);
+        assert!(!bar.update()?);
+        assert!(!bar.update()?);
+        assert!(bar.update()?);
+        assert!(bar.update()?);
+        Ok(())
+    }
+
+    #[test]
+    fn scaled_and_repeated() -> Result<(), MockedOutputPinError> {
+        let pins = [
+            MockedOutputPin::expected(4, 0b0101_u128),
+            MockedOutputPin::expected(4, 0b0000_u128),
+        ];
+        let mut bar = SegmentOutput::new(pins, 2);
+        bar.set(Frames::blink(0b01), Repeat::Times(1));
+        for _ in 1..8 {
+            assert!(!bar.update()?);
+        }
+        assert!(bar.update()?);
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_frames() {
+        let mut bar = SegmentOutput::new([MockedOutputPin::expected(0, 0b0_u128)], 1);
+        bar.set(Frames::empty(), Repeat::Never);
+    }
+}
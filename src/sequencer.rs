@@ -0,0 +1,261 @@
+//! Output of multi-level state sequences
+//!
+//! `OnOffSequenceOutput` plays bits: every state is either on or off. A
+//! [`SymbolSequenceOutput`] plays symbols of 1 up to 8 bits instead, e.g. a
+//! palette index of an RGB status LED or the level of a multi-level
+//! indicator. What a symbol means is up to a [`SymbolOutput`], which maps each
+//! symbol to pin operations.
+//!
+//! With one bit per symbol and an [`OnOff`] output it behaves like
+//! `OnOffSequenceOutput`, the `u128` on/off pattern is the special case.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use on_off_sequence_output::prelude::*;
+//! use on_off_sequence_output::sequencer::{Rgb, SymbolSequenceOutput};
+//!
+//! // three bits per symbol: bit 0 red, bit 1 green, bit 2 blue
+//! const OFF: u8 = 0b000;
+//! const RED: u8 = 0b001;
+//! const CYAN: u8 = 0b110;
+//!
+//! let rgb = Rgb::new(red_pin, green_pin, blue_pin);
+//! let mut led = SymbolSequenceOutput::new(rgb, 3, UPDATE_SCALE);
+//! led.set(&[RED, OFF, CYAN, OFF], Repeat::Forever);
+//! loop {
+//!    led.update().unwrap();
+//!    wait(1.ms());
+//! }
+//! ```
+
+use crate::builder::MAX_NUMBER_OF_STATES;
+use crate::progress::Progress;
+use crate::{OutputUpdate, Repeat};
+use embedded_hal::digital::v2::OutputPin;
+
+/// Maps symbols to pin operations
+pub trait SymbolOutput {
+    /// Error type of the pin operations
+    type Error;
+
+    /// Outputs a symbol, called when a new state starts
+    fn output(&mut self, symbol: u8) -> Result<(), Self::Error>;
+
+    /// Called on every update a state lasts longer, e.g. to alternate
+    /// colors; does nothing by default
+    fn refresh(&mut self, _symbol: u8) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// A single pin: symbol 0 is off, every other symbol on
+pub struct OnOff<T: OutputPin>(pub T);
+
+impl<T: OutputPin> SymbolOutput for OnOff<T> {
+    type Error = T::Error;
+
+    fn output(&mut self, symbol: u8) -> Result<(), Self::Error> {
+        if symbol == 0 {
+            self.0.set_low()
+        } else {
+            self.0.set_high()
+        }
+    }
+}
+
+/// RGB LED on three pins: bit 0 of a symbol is red, bit 1 green, bit 2 blue
+pub struct Rgb<R, G, B>
+where
+    R: OutputPin,
+    G: OutputPin<Error = R::Error>,
+    B: OutputPin<Error = R::Error>,
+{
+    /// The red pin, lit if high
+    pub red: R,
+    /// The green pin, lit if high
+    pub green: G,
+    /// The blue pin, lit if high
+    pub blue: B,
+}
+
+impl<R, G, B> Rgb<R, G, B>
+where
+    R: OutputPin,
+    G: OutputPin<Error = R::Error>,
+    B: OutputPin<Error = R::Error>,
+{
+    /// Combines the pins of an RGB LED
+    pub fn new(red: R, green: G, blue: B) -> Self {
+        Self { red, green, blue }
+    }
+}
+
+impl<R, G, B> SymbolOutput for Rgb<R, G, B>
+where
+    R: OutputPin,
+    G: OutputPin<Error = R::Error>,
+    B: OutputPin<Error = R::Error>,
+{
+    type Error = R::Error;
+
+    fn output(&mut self, symbol: u8) -> Result<(), Self::Error> {
+        fn drive<P: OutputPin>(pin: &mut P, high: bool) -> Result<(), P::Error> {
+            if high {
+                pin.set_high()
+            } else {
+                pin.set_low()
+            }
+        }
+        drive(&mut self.red, symbol & 0b001 != 0)?;
+        drive(&mut self.green, symbol & 0b010 != 0)?;
+        drive(&mut self.blue, symbol & 0b100 != 0)
+    }
+}
+
+/// Maximum number of symbols in a sequence
+///
+/// # Arguments
+///
+/// * `bits_per_symbol` - 1 up to 8
+pub const fn max_number_of_symbols(bits_per_symbol: u8) -> u16 {
+    if bits_per_symbol == 1 {
+        // like `OnOffSequenceOutput::set()`
+        MAX_NUMBER_OF_STATES
+    } else {
+        128 / bits_per_symbol as u16
+    }
+}
+
+/// Output of a sequence of multi-level states
+pub struct SymbolSequenceOutput<O: SymbolOutput> {
+    /// The output the symbols are mapped by
+    pub output: O,
+
+    /// Bits per symbol, 1 up to 8
+    bits_per_symbol: u8,
+
+    /// The symbols packed into an unsigned 128-bit integer, the first
+    /// symbol in the lowest bits
+    symbols: u128,
+
+    /// Scaling, position and repetitions of the symbols
+    progress: Progress,
+
+    /// The symbol currently output
+    shown: Option<u8>,
+}
+
+impl<O: SymbolOutput> SymbolSequenceOutput<O> {
+    /// Initializes a new symbol sequence output
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - Maps the symbols to pin operations
+    /// * `bits_per_symbol` - 1 up to 8
+    /// * `update_scale` - Scale factor:
+    ///   state change frequency = update frequency * update_scale
+    ///
+    /// # Notes
+    ///
+    /// * Default is symbol 0 once
+    ///
+    /// # Panics
+    ///
+    /// If the bits per symbol are not in the range 1 up to 8
+    pub fn new(output: O, bits_per_symbol: u8, update_scale: u16) -> Self {
+        if bits_per_symbol == 0 || bits_per_symbol > 8 {
+            panic!("Must be 1 up to 8 bits per symbol");
+        };
+        Self {
+            output,
+            bits_per_symbol,
+            symbols: 0,
+            progress: Progress::new(update_scale, 1, Repeat::Never),
+            shown: None,
+        }
+    }
+
+    /// Set a new symbol sequence
+    ///
+    /// # Arguments
+    ///
+    /// * `symbols` - Symbols in the order they are output
+    /// * `repeat` - How often is the sequence repeated
+    ///
+    /// # Panics
+    ///
+    /// If there are no symbols, more than `max_number_of_symbols()` or a
+    /// symbol does not fit into the bits per symbol
+    pub fn set(&mut self, symbols: &[u8], repeat: Repeat) {
+        if symbols.len() > max_number_of_symbols(self.bits_per_symbol) as usize {
+            panic!("Too many symbols for the bits per symbol");
+        };
+        let bits = u32::from(self.bits_per_symbol);
+        let mut packed = 0u128;
+        // the first symbol ends up in the lowest bits
+        for &symbol in symbols.iter().rev() {
+            if u32::from(symbol) >> bits != 0 {
+                panic!("Symbol does not fit into the bits per symbol");
+            }
+            packed = (packed << bits) | u128::from(symbol);
+        }
+        self.set_packed(packed, symbols.len() as u16, repeat);
+    }
+
+    /// Set a new symbol sequence packed into an unsigned 128-bit integer
+    ///
+    /// # Arguments
+    ///
+    /// * `symbols` - Symbols of bits per symbol each, the first symbol in
+    ///   the lowest bits
+    /// * `number_of_symbols` - How many symbols are considered
+    /// * `repeat` - How often is the sequence repeated
+    ///
+    /// # Panics
+    ///
+    /// If there are no symbols or more than `max_number_of_symbols()`
+    pub fn set_packed(&mut self, symbols: u128, number_of_symbols: u16, repeat: Repeat) {
+        if number_of_symbols > max_number_of_symbols(self.bits_per_symbol) {
+            panic!("Too many symbols for the bits per symbol");
+        };
+        if number_of_symbols == 0 {
+            panic!("Zero symbols do not make sense");
+        };
+        self.symbols = symbols;
+        self.progress.restart(number_of_symbols, repeat);
+    }
+
+    /// check which symbol is at a certain position
+    fn symbol_at_position(&self, position: u16) -> u8 {
+        let bits = u32::from(self.bits_per_symbol);
+        let mask = (1u128 << bits) - 1;
+        ((self.symbols >> (u32::from(position) * bits)) & mask) as u8
+    }
+}
+
+impl<O: SymbolOutput> OutputUpdate for SymbolSequenceOutput<O> {
+    type Error = O::Error;
+
+    /// Updates the output logic, potentially outputs the next symbol or
+    /// refreshes the current one
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        if self.progress.tick() {
+            if let Some(state_index) = self.progress.current() {
+                let symbol = self.symbol_at_position(state_index);
+                self.shown = Some(symbol);
+                self.output.output(symbol)?;
+                self.progress.advance();
+                return Ok(self.progress.is_completed());
+            }
+            self.progress.advance();
+        }
+        if let Some(symbol) = self.shown {
+            self.output.refresh(symbol)?;
+        }
+        Ok(self.progress.is_completed())
+    }
+}
+
+#[cfg(test)]
+mod tests;
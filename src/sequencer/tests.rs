@@ -0,0 +1,139 @@
+/// Tests for the multi-level state sequences
+use super::*;
+use crate::tests::mocks::*;
+
+/// Records the output symbols, 4 bits each, and the number of refreshes
+#[derive(Default)]
+struct Recorder {
+    symbols: u128,
+    number_of_symbols: u32,
+    refreshes: u16,
+}
+
+impl SymbolOutput for Recorder {
+    type Error = MockedOutputPinError;
+
+    fn output(&mut self, symbol: u8) -> Result<(), Self::Error> {
+        self.symbols |= u128::from(symbol) << (4 * self.number_of_symbols);
+        self.number_of_symbols += 1;
+        Ok(())
+    }
+
+    fn refresh(&mut self, _symbol: u8) -> Result<(), Self::Error> {
+        self.refreshes += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn symbols() -> Result<(), MockedOutputPinError> {
+    let mut sequence = SymbolSequenceOutput::new(Recorder::default(), 4, 1);
+    sequence.set(&[0x3, 0xf, 0x0, 0x9], Repeat::Never);
+    for _ in 1..4 {
+        assert!(!sequence.update()?);
+    }
+    assert!(sequence.update()?);
+    assert!(sequence.update()?);
+    assert_eq!(0x90f3, sequence.output.symbols);
+    assert_eq!(4, sequence.output.number_of_symbols);
+    Ok(())
+}
+
+#[test]
+fn packed_symbols() -> Result<(), MockedOutputPinError> {
+    let mut sequence = SymbolSequenceOutput::new(Recorder::default(), 2, 1);
+    sequence.set_packed(0b11_10_01, 3, Repeat::Never);
+    for _ in 0..3 {
+        sequence.update()?;
+    }
+    assert_eq!(0x321, sequence.output.symbols);
+    Ok(())
+}
+
+#[test]
+fn refresh_while_state_lasts() -> Result<(), MockedOutputPinError> {
+    let mut sequence = SymbolSequenceOutput::new(Recorder::default(), 2, 3);
+    sequence.set(&[0b01, 0b10], Repeat::Never);
+    // nothing output yet, nothing to refresh
+    sequence.update()?;
+    sequence.update()?;
+    assert_eq!(0, sequence.output.refreshes);
+    for _ in 0..4 {
+        sequence.update()?;
+    }
+    assert_eq!(2, sequence.output.number_of_symbols);
+    assert_eq!(2, sequence.output.refreshes);
+    Ok(())
+}
+
+#[test]
+fn repetitions() -> Result<(), MockedOutputPinError> {
+    let mut sequence = SymbolSequenceOutput::new(Recorder::default(), 3, 1);
+    sequence.set(&[0b101, 0b010], Repeat::Times(2));
+    for _ in 1..6 {
+        assert!(!sequence.update()?);
+    }
+    assert!(sequence.update()?);
+    assert_eq!(0x25_25_25, sequence.output.symbols);
+    Ok(())
+}
+
+#[test]
+fn on_off_as_special_case() -> Result<(), MockedOutputPinError> {
+    let pin = MockedOutputPin::expected(8, 0b1001_1101_u128);
+    let mut sequence = SymbolSequenceOutput::new(OnOff(pin), 1, 1);
+    sequence.set_packed(0b1001_1101, 8, Repeat::Never);
+    for _ in 0..8 {
+        sequence.update()?;
+    }
+    Ok(())
+}
+
+#[test]
+fn rgb() -> Result<(), MockedOutputPinError> {
+    let red = MockedOutputPin::expected(3, 0b101_u128);
+    let green = MockedOutputPin::expected(3, 0b110_u128);
+    let blue = MockedOutputPin::expected(3, 0b100_u128);
+    let mut sequence = SymbolSequenceOutput::new(Rgb::new(red, green, blue), 3, 1);
+    sequence.set(&[0b001, 0b010, 0b111], Repeat::Never);
+    for _ in 0..3 {
+        sequence.update()?;
+    }
+    Ok(())
+}
+
+#[test]
+fn max_number_of_symbols_per_width() {
+    assert_eq!(127, max_number_of_symbols(1));
+    assert_eq!(64, max_number_of_symbols(2));
+    assert_eq!(42, max_number_of_symbols(3));
+    assert_eq!(32, max_number_of_symbols(4));
+    assert_eq!(16, max_number_of_symbols(8));
+}
+
+#[test]
+#[should_panic]
+fn symbol_too_wide() {
+    let mut sequence = SymbolSequenceOutput::new(Recorder::default(), 2, 1);
+    sequence.set(&[0b100], Repeat::Never);
+}
+
+#[test]
+#[should_panic]
+fn too_many_symbols() {
+    let mut sequence = SymbolSequenceOutput::new(Recorder::default(), 4, 1);
+    sequence.set(&[0; 33], Repeat::Never);
+}
+
+#[test]
+#[should_panic]
+fn no_symbols() {
+    let mut sequence = SymbolSequenceOutput::new(Recorder::default(), 4, 1);
+    sequence.set(&[], Repeat::Never);
+}
+
+#[test]
+#[should_panic]
+fn zero_bits_per_symbol() {
+    SymbolSequenceOutput::new(Recorder::default(), 0, 1);
+}
@@ -0,0 +1,185 @@
+//! Output shared between execution contexts
+//!
+//! `set()` is typically called from one context (main loop, UART handler)
+//! while `update()` runs in another one (SysTick interrupt). A
+//! [`SharedOutput`] guards the output by a critical section, so changing the
+//! pattern never tears `output_states` and `number_of_output_states`.
+//!
+//! Requires the `critical-section` feature and a critical section
+//! implementation for the target, e.g. of the `cortex-m` crate.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use on_off_sequence_output::prelude::*;
+//! use on_off_sequence_output::shared::SharedOutput;
+//!
+//! static LED: SharedOutput<LedPin> = SharedOutput::new();
+//!
+//! fn main() {
+//!     LED.init(OnOffSequenceOutput::new(led_pin, 100));
+//!     // ...
+//! }
+//!
+//! #[interrupt]
+//! fn USART1() {
+//!     LED.lock(|led| led.set(0b0101, 4, Repeat::Times(3)));
+//! }
+//!
+//! #[exception]
+//! fn SysTick() {
+//!     (&LED).update().unwrap();
+//! }
+//! ```
+//!
+//! With RTIC the shared output can be [split](SharedOutput::split) into a
+//! setter and an updater handle, one for each task.
+
+use crate::dsl::DslError;
+use crate::morse::MorseError;
+use crate::{OnOffSequenceOutput, OutputUpdate, Repeat};
+use core::cell::RefCell;
+use critical_section::Mutex;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Output guarded by a critical section
+pub struct SharedOutput<T: OutputPin> {
+    output: Mutex<RefCell<Option<OnOffSequenceOutput<T>>>>,
+}
+
+impl<T: OutputPin> SharedOutput<T> {
+    /// Creates a shared output without an output installed
+    ///
+    /// # Notes
+    ///
+    /// * `const`, so it can be used to initialize a `static`
+    /// * Install the output by `init()`
+    pub const fn new() -> Self {
+        Self {
+            output: Mutex::new(RefCell::new(None)),
+        }
+    }
+
+    /// Installs the output, a previously installed output is dropped
+    pub fn init(&self, output: OnOffSequenceOutput<T>) {
+        critical_section::with(|cs| {
+            self.output.borrow(cs).replace(Some(output));
+        });
+    }
+
+    /// Removes the output, e.g. to get back the pin
+    pub fn release(&self) -> Option<OnOffSequenceOutput<T>> {
+        critical_section::with(|cs| self.output.borrow(cs).take())
+    }
+
+    /// Runs a closure on the output within a critical section
+    ///
+    /// # Returns
+    ///
+    /// * Some(result of the closure)
+    /// * None - if no output is installed
+    pub fn lock<R>(&self, f: impl FnOnce(&mut OnOffSequenceOutput<T>) -> R) -> Option<R> {
+        critical_section::with(|cs| self.output.borrow(cs).borrow_mut().as_mut().map(f))
+    }
+
+    /// Splits into a setter handle for the producer and an updater handle
+    /// for the context calling `update()`
+    pub fn split(&self) -> (OutputSetter<'_, T>, OutputUpdater<'_, T>) {
+        (
+            OutputSetter { shared: self },
+            OutputUpdater { shared: self },
+        )
+    }
+}
+
+impl<T: OutputPin> Default for SharedOutput<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: OutputPin> OutputUpdate for &SharedOutput<T> {
+    type Error = T::Error;
+
+    /// Updates the installed output within a critical section
+    ///
+    /// Returns true if no output is installed, there is nothing to do.
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        self.lock(|output| output.update()).unwrap_or(Ok(true))
+    }
+
+    /// Updates the installed output within a critical section
+    ///
+    /// Returns true if no output is installed, there is nothing to do.
+    fn update_by(&mut self, ticks: u16) -> Result<bool, Self::Error> {
+        self.lock(|output| output.update_by(ticks))
+            .unwrap_or(Ok(true))
+    }
+}
+
+/// Producer handle of a [`SharedOutput`], it can only change the pattern
+///
+/// Like `lock()`, all methods return None and have no effect if no output
+/// is installed.
+pub struct OutputSetter<'a, T: OutputPin> {
+    shared: &'a SharedOutput<T>,
+}
+
+impl<T: OutputPin> OutputSetter<'_, T> {
+    /// Set a new output, see `OnOffSequenceOutput::set()`
+    ///
+    /// # Returns
+    ///
+    /// None if no output is installed
+    pub fn set(
+        &self,
+        output_states: u128,
+        number_of_output_states: u16,
+        repeat: Repeat,
+    ) -> Option<()> {
+        self.shared
+            .lock(|output| output.set(output_states, number_of_output_states, repeat))
+    }
+
+    /// Set a new morse code as output, see `OnOffSequenceOutput::set_morse()`
+    ///
+    /// # Returns
+    ///
+    /// * Some(result of `set_morse()`)
+    /// * None - if no output is installed
+    pub fn set_morse(&self, morse_text: &str, repeat: Repeat) -> Option<Result<(), MorseError>> {
+        self.shared
+            .lock(|output| output.set_morse(morse_text, repeat))
+    }
+
+    /// Set a new output in text notation, see `OnOffSequenceOutput::set_dsl()`
+    ///
+    /// # Returns
+    ///
+    /// * Some(result of `set_dsl()`)
+    /// * None - if no output is installed
+    pub fn set_dsl(&self, pattern_text: &str, repeat: Repeat) -> Option<Result<(), DslError>> {
+        self.shared
+            .lock(|output| output.set_dsl(pattern_text, repeat))
+    }
+}
+
+/// Consumer handle of a [`SharedOutput`], it can only update the output
+pub struct OutputUpdater<'a, T: OutputPin> {
+    shared: &'a SharedOutput<T>,
+}
+
+impl<T: OutputPin> OutputUpdate for OutputUpdater<'_, T> {
+    type Error = T::Error;
+
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        (&mut self.shared).update()
+    }
+
+    fn update_by(&mut self, ticks: u16) -> Result<bool, Self::Error> {
+        (&mut self.shared).update_by(ticks)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,91 @@
+/// Tests for the output shared between execution contexts
+use super::*;
+use crate::tests::mocks::*;
+extern crate std;
+
+#[test]
+fn update_without_output() -> Result<(), MockedOutputPinError> {
+    let shared: SharedOutput<MockedOutputPin> = SharedOutput::new();
+    assert!((&shared).update()?);
+    assert!((&shared).update_by(3)?);
+    assert_eq!(None, shared.lock(|_| ()));
+    Ok(())
+}
+
+#[test]
+fn lock_and_update() -> Result<(), MockedOutputPinError> {
+    let shared = SharedOutput::new();
+    shared.init(OnOffSequenceOutput::new(
+        MockedOutputPin::expected(3, 0b101_u128),
+        1,
+    ));
+    shared.lock(|led| led.set(0b101, 3, Repeat::Never));
+    let mut updater = &shared;
+    assert!(!updater.update()?);
+    assert!(updater.update_by(2)?);
+    Ok(())
+}
+
+#[test]
+fn release_returns_output() {
+    let shared = SharedOutput::new();
+    shared.init(OnOffSequenceOutput::new(
+        MockedOutputPin::expected(0, 0b0_u128),
+        1,
+    ));
+    assert!(shared.release().is_some());
+    assert!(shared.release().is_none());
+}
+
+#[test]
+fn split_into_setter_and_updater() -> Result<(), MockedOutputPinError> {
+    let shared = SharedOutput::new();
+    shared.init(OnOffSequenceOutput::new(
+        MockedOutputPin::expected(6, 0b00_0111_u128),
+        1,
+    ));
+    let (setter, mut updater) = shared.split();
+    assert_eq!(Some(()), setter.set(0b0111, 4, Repeat::Never));
+    for _ in 0..4 {
+        updater.update()?;
+    }
+    assert_eq!(
+        Some(Err(DslError::UnexpectedCharacter)),
+        setter.set_dsl("?", Repeat::Never)
+    );
+    assert_eq!(
+        Some(Err(MorseError::UnsupportedCode)),
+        setter.set_morse("?", Repeat::Never)
+    );
+    assert_eq!(Some(Ok(())), setter.set_dsl("__", Repeat::Never));
+    assert!(!updater.update()?);
+    assert!(updater.update()?);
+    Ok(())
+}
+
+#[test]
+fn setter_without_output() {
+    let shared: SharedOutput<MockedOutputPin> = SharedOutput::new();
+    let (setter, _) = shared.split();
+    assert_eq!(None, setter.set(0b1, 1, Repeat::Never));
+    assert_eq!(None, setter.set_morse("SOS", Repeat::Never));
+    assert_eq!(None, setter.set_dsl("#_", Repeat::Never));
+}
+
+#[test]
+fn update_from_other_thread() {
+    static SHARED: SharedOutput<MockedOutputPin> = SharedOutput::new();
+    SHARED.init(OnOffSequenceOutput::new(
+        MockedOutputPin::expected(2, 0b11_u128),
+        1,
+    ));
+    SHARED.lock(|led| led.set(0b11, 2, Repeat::Never));
+    std::thread::spawn(|| {
+        let mut updater = &SHARED;
+        while !updater.update().unwrap() {}
+    })
+    .join()
+    .unwrap();
+    // dropping the pin evaluates the expectations
+    drop(SHARED.release());
+}
@@ -1,16 +1,14 @@
 use crate::prelude::*;
 use embedded_hal::digital::v2::OutputPin;
-use failure::Fail;
 use mocks::*;
 
 // Mock implementations.
 // Note: mockall crate does not help in no_std environment (yet)
 //       so we do it the manually
-mod mocks {
+pub(crate) mod mocks {
     use super::*;
 
-    #[derive(Debug, Fail)]
-    #[fail(display = "An error occurred")]
+    #[derive(Debug)]
     pub struct MockedOutputPinError;
 
     fn set_bit(input: u128, bit: u16) -> u128 {
@@ -42,6 +40,8 @@ mod mocks {
         collected_no_of_calls: u16,
         expected_states: u128,
         expected_no_of_calls: u16,
+        /// Calls failing with an error, bit 0 is the first call
+        failing_calls: u128,
     }
 
     impl MockedOutputPin {
@@ -51,6 +51,28 @@ mod mocks {
                 collected_no_of_calls: 0_u16,
                 expected_states,
                 expected_no_of_calls,
+                failing_calls: 0_u128,
+            }
+        }
+
+        /// Like `expected()`, but the calls given by the set bits of
+        /// `failing_calls` return an error; the attempted states are
+        /// collected nevertheless
+        pub fn failing(
+            expected_no_of_calls: u16,
+            expected_states: u128,
+            failing_calls: u128,
+        ) -> Self {
+            let mut pin = Self::expected(expected_no_of_calls, expected_states);
+            pin.failing_calls = failing_calls;
+            pin
+        }
+
+        fn result(&self, call: u16) -> Result<(), MockedOutputPinError> {
+            if self.failing_calls & (1_u128 << call) != 0 {
+                Err(MockedOutputPinError)
+            } else {
+                Ok(())
             }
         }
     }
@@ -58,15 +80,17 @@ mod mocks {
         type Error = MockedOutputPinError;
 
         fn set_high(&mut self) -> Result<(), MockedOutputPinError> {
-            self.collected_states = set_bit(self.collected_states, self.collected_no_of_calls);
+            let call = self.collected_no_of_calls;
+            self.collected_states = set_bit(self.collected_states, call);
             self.collected_no_of_calls += 1;
-            Ok(())
+            self.result(call)
         }
 
         fn set_low(&mut self) -> Result<(), MockedOutputPinError> {
-            self.collected_states = reset_bit(self.collected_states, self.collected_no_of_calls);
+            let call = self.collected_no_of_calls;
+            self.collected_states = reset_bit(self.collected_states, call);
             self.collected_no_of_calls += 1;
-            Ok(())
+            self.result(call)
         }
     }
 
@@ -123,6 +147,142 @@ mod on_off_sequence_output {
         }
     }
 
+    mod is_completed {
+        use super::*;
+
+        #[test]
+        fn after_all_repetitions() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(4, 0b0101_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set(0b01, 2, Repeat::Times(1));
+            for _ in 0..4 {
+                assert!(!ledout.is_completed());
+                ledout.update()?;
+            }
+            assert!(ledout.is_completed());
+            Ok(())
+        }
+    }
+
+    mod set_with_bit_order {
+        use super::*;
+
+        #[test]
+        fn lsb_first() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(4, 0b1110_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_with_bit_order(0b1110, 4, Repeat::Never, BitOrder::LsbFirst);
+            for _ in 0..4 {
+                ledout.update()?;
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn msb_first() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(4, 0b0111_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_with_bit_order(0b1110, 4, Repeat::Never, BitOrder::MsbFirst);
+            for _ in 1..4 {
+                assert!(!ledout.update()?);
+            }
+            assert!(ledout.update()?);
+            Ok(())
+        }
+
+        #[test]
+        fn msb_first_ignores_unconsidered_bits() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(6, 0b01_0010_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_with_bit_order(0b11_1111_0010, 3, Repeat::Times(1), BitOrder::MsbFirst);
+            for _ in 0..6 {
+                ledout.update()?;
+            }
+            Ok(())
+        }
+
+        #[test]
+        #[should_panic]
+        fn no_zero_sequence_length() {
+            let pin_mock = MockedOutputPin::expected(0, 0b0_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 4);
+            ledout.set_with_bit_order(1, 0, Repeat::Never, BitOrder::MsbFirst);
+        }
+    }
+
+    mod set_dsl {
+        use super::*;
+
+        #[test]
+        fn plays_in_reading_order() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(4, 0b1101_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_dsl("#_##", Repeat::Never).unwrap();
+            for _ in 1..4 {
+                assert!(!ledout.update()?);
+            }
+            assert!(ledout.update()?);
+            Ok(())
+        }
+
+        #[test]
+        fn invalid_text() {
+            let pin_mock = MockedOutputPin::expected(0, 0b0_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            assert_eq!(
+                Err(DslError::UnexpectedCharacter),
+                ledout.set_dsl("#?", Repeat::Never)
+            );
+        }
+    }
+
+    mod set_pattern {
+        use super::*;
+
+        #[test]
+        fn active_low_with_update_scale() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(3, 0b010_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 4);
+            let pattern = Pattern::new(0b101, 3, Repeat::Never)
+                .with_update_scale(2)
+                .with_polarity(crate::pattern::Polarity::ActiveLow);
+            ledout.set_pattern(&pattern).unwrap();
+            for _ in 1..6 {
+                assert!(!ledout.update()?);
+            }
+            assert!(ledout.update()?);
+            Ok(())
+        }
+
+        #[test]
+        fn keeps_update_scale() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(2, 0b01_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 2);
+            ledout
+                .set_pattern(&Pattern::new(0b01, 2, Repeat::Never))
+                .unwrap();
+            for _ in 1..4 {
+                assert!(!ledout.update()?);
+            }
+            assert!(ledout.update()?);
+            Ok(())
+        }
+
+        #[test]
+        fn invalid_pattern() {
+            let pin_mock = MockedOutputPin::expected(0, 0b0_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            assert_eq!(
+                Err(PatternError::InvalidCount),
+                ledout.set_pattern(&Pattern::new(0, 0, Repeat::Never))
+            );
+            assert_eq!(
+                Err(PatternError::SequenceTooLong),
+                ledout.set_pattern(&Pattern::new(0, 128, Repeat::Never))
+            );
+        }
+    }
+
     mod update_scaling {
         use super::*;
 
@@ -241,6 +401,271 @@ mod on_off_sequence_output {
         }
     }
 
+    mod update_by {
+        use super::*;
+
+        #[test]
+        fn zero_ticks() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(0, 0b0_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set(0b1, 1, Repeat::Never);
+            assert!(!ledout.update_by(0)?);
+            Ok(())
+        }
+
+        /// Counts the updates, uses the default `update_by()`
+        struct CountingOutput(u16);
+
+        impl OutputUpdate for CountingOutput {
+            type Error = MockedOutputPinError;
+
+            fn update(&mut self) -> Result<bool, Self::Error> {
+                self.0 += 1;
+                Ok(self.0 >= 3)
+            }
+        }
+
+        #[test]
+        fn default_calls_update_per_tick() -> Result<(), MockedOutputPinError> {
+            let mut output = CountingOutput(0);
+            assert!(!output.update_by(0)?);
+            assert_eq!(0, output.0);
+            assert!(!output.update_by(2)?);
+            assert_eq!(2, output.0);
+            assert!(output.update_by(1)?);
+            Ok(())
+        }
+
+        #[test]
+        fn one_tick_equals_update() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(2, 0b10_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 2);
+            ledout.set(0b10, 2, Repeat::Never);
+            for _ in 1..4 {
+                assert!(!ledout.update_by(1)?);
+            }
+            assert!(ledout.update_by(1)?);
+            Ok(())
+        }
+
+        #[test]
+        fn remainder_is_kept() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(2, 0b10_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 4);
+            ledout.set(0b10, 2, Repeat::Never);
+            // 6 ticks: one state change, two ticks left
+            assert!(!ledout.update_by(6)?);
+            // 2 ticks: together with the left ones the second state change
+            assert!(ledout.update_by(2)?);
+            Ok(())
+        }
+
+        #[test]
+        fn replay_edges() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(5, 0b1_1001_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 2);
+            ledout.set(0b1_1001, 5, Repeat::Never);
+            assert!(!ledout.update_by(7)?);
+            assert!(ledout.update_by(3)?);
+            Ok(())
+        }
+
+        #[test]
+        fn replay_edges_equals_updates() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(8, 0b1110_1110_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 3);
+            ledout.set(0b1110, 4, Repeat::Times(1));
+            ledout.update_by(1)?;
+            for _ in 1..9 {
+                ledout.update()?;
+            }
+            assert!(!ledout.update_by(12)?);
+            assert!(ledout.update_by(3)?);
+            Ok(())
+        }
+
+        #[test]
+        fn replay_edges_pin_error_ends_burst() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::failing(5, 0b0_1001_u128, 0b10);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set(0b0101, 4, Repeat::Never);
+            // the second state fails, the third one is not caught up
+            assert!(ledout.update_by(3).is_err());
+            assert!(!ledout.update_by(1)?);
+            assert!(!ledout.update_by(1)?);
+            assert!(ledout.update_by(1)?);
+            Ok(())
+        }
+
+        #[test]
+        fn skip_to_current() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(2, 0b01_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_catch_up(CatchUp::SkipToCurrent);
+            ledout.set(0b0110, 4, Repeat::Never);
+            // states 0..=2 elapsed, only state 2 is driven
+            assert!(!ledout.update_by(3)?);
+            assert!(ledout.update_by(1)?);
+            Ok(())
+        }
+
+        #[test]
+        fn skip_to_current_across_repetitions() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(2, 0b01_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_catch_up(CatchUp::SkipToCurrent);
+            ledout.set(0b011, 3, Repeat::Forever);
+            // 3 states of the first run and the first state of the second run
+            assert!(!ledout.update_by(4)?);
+            // second and third state of the second run
+            assert!(!ledout.update_by(2)?);
+            Ok(())
+        }
+
+        #[test]
+        fn skip_to_current_drives_last_state_when_done() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(1, 0b1_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_catch_up(CatchUp::SkipToCurrent);
+            ledout.set(0b100, 3, Repeat::Never);
+            assert!(ledout.update_by(100)?);
+            Ok(())
+        }
+    }
+
+    mod pin_writes {
+        use super::*;
+
+        #[test]
+        fn every_state_by_default() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(4, 0b0011_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set(0b0011, 4, Repeat::Never);
+            for _ in 0..4 {
+                ledout.update()?;
+            }
+            assert_eq!(Some(false), ledout.driven_level());
+            Ok(())
+        }
+
+        #[test]
+        fn edges_only() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(4, 0b0101_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_pin_writes(PinWrites::EdgesOnly { refresh: 0 });
+            ledout.set(0b0011, 4, Repeat::Times(1));
+            for _ in 0..8 {
+                ledout.update()?;
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn edges_only_with_refresh() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::expected(3, 0b111_u128);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_pin_writes(PinWrites::EdgesOnly { refresh: 2 });
+            ledout.set(0b11111, 5, Repeat::Never);
+            for _ in 0..5 {
+                ledout.update()?;
+            }
+            Ok(())
+        }
+
+        #[test]
+        fn edges_only_writes_again_after_error() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::failing(2, 0b11_u128, 0b1);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_error_policy(ErrorPolicy::Ignore);
+            ledout.set_pin_writes(PinWrites::EdgesOnly { refresh: 0 });
+            ledout.set(0b111, 3, Repeat::Never);
+            ledout.update()?;
+            assert_eq!(None, ledout.driven_level());
+            ledout.update()?;
+            ledout.update()?;
+            assert_eq!(Some(true), ledout.driven_level());
+            Ok(())
+        }
+    }
+
+    mod error_policy {
+        use super::*;
+
+        #[test]
+        fn propagate_waits_a_state_duration() {
+            let pin_mock = MockedOutputPin::failing(3, 0b011_u128, 0b1);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 2);
+            ledout.set(0b01, 2, Repeat::Never);
+            assert!(matches!(ledout.update(), Ok(false)));
+            assert!(ledout.update().is_err());
+            assert!(matches!(ledout.update(), Ok(false)));
+            assert!(matches!(ledout.update(), Ok(false)));
+            assert!(matches!(ledout.update(), Ok(false)));
+            assert!(matches!(ledout.update(), Ok(true)));
+            assert_eq!(1, ledout.error_count());
+        }
+
+        #[test]
+        fn retry_next_tick() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::failing(3, 0b011_u128, 0b1);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 2);
+            ledout.set_error_policy(ErrorPolicy::RetryNextTick);
+            ledout.set(0b01, 2, Repeat::Never);
+            assert!(!ledout.update()?);
+            assert!(!ledout.update()?);
+            assert!(!ledout.update()?);
+            assert!(!ledout.update()?);
+            assert!(ledout.update()?);
+            assert_eq!(1, ledout.error_count());
+            Ok(())
+        }
+
+        #[test]
+        fn retry_next_tick_by_update_by() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::failing(3, 0b011_u128, 0b1);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_error_policy(ErrorPolicy::RetryNextTick);
+            ledout.set(0b01, 2, Repeat::Never);
+            assert!(!ledout.update_by(2)?);
+            assert!(ledout.update_by(2)?);
+            assert_eq!(1, ledout.error_count());
+            Ok(())
+        }
+
+        #[test]
+        fn ignore() -> Result<(), MockedOutputPinError> {
+            let pin_mock = MockedOutputPin::failing(4, 0b0101_u128, 0b0101);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_error_policy(ErrorPolicy::Ignore);
+            ledout.set(0b01, 2, Repeat::Times(1));
+            assert!(!ledout.update()?);
+            assert!(!ledout.update()?);
+            assert!(!ledout.update()?);
+            assert!(ledout.update()?);
+            assert_eq!(2, ledout.error_count());
+            ledout.reset_error_count();
+            assert_eq!(0, ledout.error_count());
+            Ok(())
+        }
+
+        #[test]
+        fn latch_fault() {
+            let pin_mock = MockedOutputPin::failing(3, 0b011_u128, 0b1);
+            let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+            ledout.set_error_policy(ErrorPolicy::LatchFault);
+            ledout.set(0b01, 2, Repeat::Never);
+            assert!(ledout.update().is_err());
+            assert!(ledout.is_faulted());
+            assert!(matches!(ledout.update(), Ok(true)));
+            assert!(matches!(ledout.update_by(5), Ok(true)));
+            ledout.clear_fault();
+            assert!(!ledout.is_faulted());
+            assert!(matches!(ledout.update(), Ok(false)));
+            assert!(matches!(ledout.update(), Ok(true)));
+            assert_eq!(1, ledout.error_count());
+        }
+    }
+
     mod update_repeats {
         use super::*;
 
@@ -305,46 +730,187 @@ mod on_off_sequence_output {
     }
 }
 
+mod bit_order {
+    use super::*;
+
+    #[test]
+    fn lsb_first_is_unchanged() {
+        assert_eq!(0b1110, BitOrder::LsbFirst.to_lsb_first(0b1110, 4));
+    }
+
+    #[test]
+    fn msb_first_is_reversed() {
+        assert_eq!(0b0111, BitOrder::MsbFirst.to_lsb_first(0b1110, 4));
+        assert_eq!(0b0_0011, BitOrder::MsbFirst.to_lsb_first(0b1_1000, 5));
+        assert_eq!(0b0_0111, BitOrder::MsbFirst.to_lsb_first(0b0_1110, 4));
+        assert_eq!(0b1, BitOrder::MsbFirst.to_lsb_first(0b1, 1));
+    }
+
+    #[test]
+    fn msb_first_full_width() {
+        assert_eq!(1, BitOrder::MsbFirst.to_lsb_first(1 << 127, 128));
+        assert_eq!(0, BitOrder::MsbFirst.to_lsb_first(!0, 0));
+    }
+}
+
 mod fn_position_of_highest_one {
     use super::super::position_of_highest_one;
 
     #[test]
     fn all_zeros() {
-        assert_eq!(0, position_of_highest_one(0));
+        assert_eq!(None, position_of_highest_one(0));
     }
 
     #[test]
     fn one_at_pos_zero() {
-        assert_eq!(0, position_of_highest_one(0b1));
+        assert_eq!(Some(0), position_of_highest_one(0b1));
     }
 
     #[test]
     fn one_at_pos_one() {
-        assert_eq!(1, position_of_highest_one(0b10));
+        assert_eq!(Some(1), position_of_highest_one(0b10));
     }
 
     #[test]
     fn one_at_various_positions() {
-        assert_eq!(2, position_of_highest_one(0b101));
-        assert_eq!(3, position_of_highest_one(0b1010));
-        assert_eq!(3, position_of_highest_one(0b1000));
+        assert_eq!(Some(2), position_of_highest_one(0b101));
+        assert_eq!(Some(3), position_of_highest_one(0b1010));
+        assert_eq!(Some(3), position_of_highest_one(0b1000));
     }
 
     #[test]
     fn one_at_various_high_positions() {
-        assert_eq!(8, position_of_highest_one(0x1ff));
-        assert_eq!(8, position_of_highest_one(0x100));
-        assert_eq!(16, position_of_highest_one(0x1_0000));
-        assert_eq!(32, position_of_highest_one(0x1_0000_0000));
-        assert_eq!(64, position_of_highest_one(0x1_0000_0000_0000_0000));
+        assert_eq!(Some(8), position_of_highest_one(0x1ff));
+        assert_eq!(Some(8), position_of_highest_one(0x100));
+        assert_eq!(Some(16), position_of_highest_one(0x1_0000));
+        assert_eq!(Some(32), position_of_highest_one(0x1_0000_0000));
+        assert_eq!(Some(64), position_of_highest_one(0x1_0000_0000_0000_0000));
         assert_eq!(
-            96,
+            Some(96),
             position_of_highest_one(0x1_0000_0000_0000_0000_0000_0000)
         );
     }
 
     #[test]
     fn all_one() {
-        assert_eq!(127, position_of_highest_one(!0));
+        assert_eq!(Some(127), position_of_highest_one(!0));
+    }
+}
+
+mod fn_number_of_states {
+    use super::super::number_of_states;
+
+    #[test]
+    fn counts_up_to_highest_one() {
+        assert_eq!(1, number_of_states(0b1, 0));
+        assert_eq!(4, number_of_states(0b1100, 0));
+        assert_eq!(4, number_of_states(0b1111, 0));
+        assert_eq!(128, number_of_states(!0, 0));
+    }
+
+    #[test]
+    fn adds_trailing_off_states() {
+        assert_eq!(6, number_of_states(0b1100, 2));
+        assert_eq!(2, number_of_states(0b1, 1));
+    }
+
+    #[test]
+    fn all_zeros() {
+        assert_eq!(0, number_of_states(0, 0));
+        assert_eq!(3, number_of_states(0, 3));
+    }
+
+    #[test]
+    fn saturates() {
+        assert_eq!(u16::MAX, number_of_states(1 << 127, u16::MAX));
+        assert_eq!(u16::MAX, number_of_states(0b11, u16::MAX));
+        assert_eq!(u16::MAX, number_of_states(0, u16::MAX));
+    }
+}
+
+mod macros {
+    use super::*;
+    use crate::{set_output_forever, set_output_once};
+
+    #[test]
+    fn once_plays_all_states() -> Result<(), MockedOutputPinError> {
+        let pin_mock = MockedOutputPin::expected(4, 0b1100_u128);
+        let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+        set_output_once!(ledout, 0b1100);
+        for _ in 1..4 {
+            assert!(!ledout.update()?);
+        }
+        assert!(ledout.update()?);
+        assert!(ledout.update()?);
+        Ok(())
+    }
+
+    #[test]
+    fn once_with_trailing_off_states() -> Result<(), MockedOutputPinError> {
+        let pin_mock = MockedOutputPin::expected(6, 0b00_1100_u128);
+        let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+        set_output_once!(ledout, 0b1100, 2);
+        for _ in 1..6 {
+            assert!(!ledout.update()?);
+        }
+        assert!(ledout.update()?);
+        Ok(())
+    }
+
+    #[test]
+    fn once_single_state() -> Result<(), MockedOutputPinError> {
+        let pin_mock = MockedOutputPin::expected(1, 0b1_u128);
+        let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+        set_output_once!(ledout, 0b1);
+        assert!(ledout.update()?);
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn once_zero_bitfield() {
+        let pin_mock = MockedOutputPin::expected(0, 0b0_u128);
+        let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+        set_output_once!(ledout, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Must be less than 128 output states")]
+    fn once_too_many_trailing_off_states() {
+        let pin_mock = MockedOutputPin::expected(0, 0b0_u128);
+        let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+        set_output_once!(ledout, 0b11, 65535);
+    }
+
+    #[test]
+    fn once_zero_bitfield_with_trailing_off_states() -> Result<(), MockedOutputPinError> {
+        let pin_mock = MockedOutputPin::expected(2, 0b00_u128);
+        let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+        set_output_once!(ledout, 0, 2);
+        assert!(!ledout.update()?);
+        assert!(ledout.update()?);
+        Ok(())
+    }
+
+    #[test]
+    fn forever_repeats_all_states() -> Result<(), MockedOutputPinError> {
+        let pin_mock = MockedOutputPin::expected(8, 0b1000_1000_u128);
+        let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+        set_output_forever!(ledout, 0b1000);
+        for _ in 1..=8 {
+            assert!(!ledout.update()?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn forever_with_trailing_off_states() -> Result<(), MockedOutputPinError> {
+        let pin_mock = MockedOutputPin::expected(8, 0b0001_0001_u128);
+        let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+        set_output_forever!(ledout, 0b1, 3);
+        for _ in 1..=8 {
+            assert!(!ledout.update()?);
+        }
+        Ok(())
     }
 }
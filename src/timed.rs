@@ -0,0 +1,165 @@
+//! Output driven by a hardware timer
+//!
+//! Calling `update()` at a fixed rate wastes CPU time on long states. A
+//! [`TimedOutput`] programs an embedded-hal `CountDown` timer with the
+//! duration of the current state, or of a run of states of the same level,
+//! and advances only when the timer expired. The update scale of the
+//! wrapped output is not used; the duration of one state is given instead.
+//!
+//! A pin error restarts the timer with the duration of one state, the
+//! failed state is driven again on its expiry. `ErrorPolicy::RetryNextTick`
+//! swallows the error, a fault latched by `ErrorPolicy::LatchFault` holds
+//! the output until `clear_fault()`.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use on_off_sequence_output::prelude::*;
+//! use on_off_sequence_output::timed::TimedOutput;
+//!
+//! // one state lasts 250 ms, timer time is given in microseconds
+//! let led = OnOffSequenceOutput::new(led_pin, 1);
+//! let mut led = TimedOutput::new(led, timer, |states| (states as u32 * 250_000).us());
+//!
+//! led.set(0b0000_0111, 8, Repeat::Forever);
+//!
+//! // e.g. in the timer interrupt, or polled
+//! led.update().unwrap();
+//! ```
+
+use crate::dsl::DslError;
+use crate::morse::MorseError;
+use crate::{ErrorPolicy, OnOffSequenceOutput, OutputUpdate, Repeat};
+use embedded_hal::digital::v2::OutputPin;
+use embedded_hal::timer::CountDown;
+
+/// Output advancing on timer expiry
+pub struct TimedOutput<T, C, D>
+where
+    T: OutputPin,
+    C: CountDown,
+    D: Fn(u16) -> C::Time,
+{
+    output: OnOffSequenceOutput<T>,
+    timer: C,
+    /// Converts a number of states into timer time
+    duration: D,
+    /// Whether the timer counts down a state
+    running: bool,
+}
+
+impl<T, C, D> TimedOutput<T, C, D>
+where
+    T: OutputPin,
+    C: CountDown,
+    D: Fn(u16) -> C::Time,
+{
+    /// Initializes a new timer driven output
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The output, its update scale is not used
+    /// * `timer` - A count down timer, exclusively used by this output
+    /// * `duration` - Converts a number of states into the time of the timer
+    ///
+    /// # Notes
+    ///
+    /// * Starts the timer; like for `update()` based output, the first state
+    ///   is driven after the duration of one state.
+    pub fn new(output: OnOffSequenceOutput<T>, timer: C, duration: D) -> Self {
+        let mut timed = Self {
+            output,
+            timer,
+            duration,
+            running: false,
+        };
+        timed.restart();
+        timed
+    }
+
+    fn restart(&mut self) {
+        self.timer.start((self.duration)(1));
+        self.running = true;
+    }
+
+    /// Set a new output, see `OnOffSequenceOutput::set()`
+    pub fn set(&mut self, output_states: u128, number_of_output_states: u16, repeat: Repeat) {
+        self.output
+            .set(output_states, number_of_output_states, repeat);
+        self.restart();
+    }
+
+    /// Set a new morse code as output, see `OnOffSequenceOutput::set_morse()`
+    pub fn set_morse(&mut self, morse_text: &str, repeat: Repeat) -> Result<(), MorseError> {
+        self.output.set_morse(morse_text, repeat)?;
+        self.restart();
+        Ok(())
+    }
+
+    /// Set a new output in text notation, see `OnOffSequenceOutput::set_dsl()`
+    pub fn set_dsl(&mut self, pattern_text: &str, repeat: Repeat) -> Result<(), DslError> {
+        self.output.set_dsl(pattern_text, repeat)?;
+        self.restart();
+        Ok(())
+    }
+
+    /// The wrapped output, e.g. to inspect it
+    pub fn output(&self) -> &OnOffSequenceOutput<T> {
+        &self.output
+    }
+
+    /// The wrapped output, e.g. to select the error policy or to clear a
+    /// fault
+    pub fn output_mut(&mut self) -> &mut OnOffSequenceOutput<T> {
+        &mut self.output
+    }
+
+    /// Splits back into the output and the timer
+    pub fn release(self) -> (OnOffSequenceOutput<T>, C) {
+        (self.output, self.timer)
+    }
+}
+
+impl<T, C, D> OutputUpdate for TimedOutput<T, C, D>
+where
+    T: OutputPin,
+    C: CountDown,
+    D: Fn(u16) -> C::Time,
+{
+    type Error = T::Error;
+
+    /// Switches the LED state if the timer expired and restarts the timer
+    /// with the duration of the new state
+    ///
+    /// # Notes
+    ///
+    /// * Can be called at any rate, e.g. from the timer interrupt only
+    /// * While faulted the output is reported as completed, it continues
+    ///   after `clear_fault()`
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        if self.output.is_faulted() {
+            return Ok(true);
+        }
+        if !self.running || self.timer.wait().is_err() {
+            return Ok(!self.running);
+        }
+        match self.output.advance_run() {
+            Ok(Some(states)) => {
+                self.timer.start((self.duration)(states));
+            }
+            Ok(None) => self.running = false,
+            Err(error) => {
+                // the failed state is driven again after one state
+                self.timer.start((self.duration)(1));
+                return match self.output.error_policy() {
+                    ErrorPolicy::RetryNextTick => Ok(false),
+                    _ => Err(error),
+                };
+            }
+        }
+        Ok(!self.running)
+    }
+}
+
+#[cfg(test)]
+mod tests;
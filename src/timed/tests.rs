@@ -0,0 +1,172 @@
+/// Tests for the timer driven output
+use super::*;
+use crate::tests::mocks::*;
+
+/// A mock implementation of `CountDown`, time is a number of states
+struct MockedTimer {
+    /// Durations the timer was started with, oldest first
+    started: [u16; 8],
+    number_of_starts: usize,
+    expired: bool,
+}
+
+impl MockedTimer {
+    fn new() -> Self {
+        Self {
+            started: [0; 8],
+            number_of_starts: 0,
+            expired: false,
+        }
+    }
+}
+
+impl CountDown for MockedTimer {
+    type Time = u16;
+
+    fn start<U: Into<u16>>(&mut self, count: U) {
+        self.started[self.number_of_starts] = count.into();
+        self.number_of_starts += 1;
+        self.expired = false;
+    }
+
+    fn wait(&mut self) -> nb::Result<(), void::Void> {
+        if self.expired {
+            self.expired = false;
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+type Timed = TimedOutput<MockedOutputPin, MockedTimer, fn(u16) -> u16>;
+
+fn timed(pin_mock: MockedOutputPin) -> Timed {
+    TimedOutput::new(
+        OnOffSequenceOutput::new(pin_mock, 100),
+        MockedTimer::new(),
+        |states| states,
+    )
+}
+
+/// Let the timer expire and update
+fn expire(ledout: &mut Timed) -> Result<bool, MockedOutputPinError> {
+    ledout.timer.expired = true;
+    ledout.update()
+}
+
+#[test]
+fn nothing_before_expiry() -> Result<(), MockedOutputPinError> {
+    let mut ledout = timed(MockedOutputPin::expected(0, 0b0_u128));
+    for _ in 0..10 {
+        assert!(!ledout.update()?);
+    }
+    assert_eq!([1, 0], ledout.timer.started[..2]);
+    Ok(())
+}
+
+#[test]
+fn runs_of_equal_states_are_merged() -> Result<(), MockedOutputPinError> {
+    let mut ledout = timed(MockedOutputPin::expected(4, 0b0101_u128));
+    ledout.set(0b0001_1100_0111, 12, Repeat::Never);
+    assert!(!expire(&mut ledout)?);
+    assert!(!expire(&mut ledout)?);
+    assert!(!expire(&mut ledout)?);
+    assert!(!expire(&mut ledout)?);
+    // the last run lasts its full duration
+    assert!(expire(&mut ledout)?);
+    assert!(ledout.update()?);
+    assert_eq!([1, 1, 3, 3, 3, 3, 0], ledout.timer.started[..7]);
+    Ok(())
+}
+
+#[test]
+fn repetitions() -> Result<(), MockedOutputPinError> {
+    let mut ledout = timed(MockedOutputPin::expected(4, 0b0101_u128));
+    ledout.set(0b001, 3, Repeat::Times(1));
+    for _ in 0..4 {
+        assert!(!expire(&mut ledout)?);
+    }
+    assert!(expire(&mut ledout)?);
+    assert_eq!([1, 1, 1, 2, 1, 2, 0], ledout.timer.started[..7]);
+    Ok(())
+}
+
+#[test]
+fn set_restarts_timer() -> Result<(), MockedOutputPinError> {
+    let mut ledout = timed(MockedOutputPin::expected(2, 0b01_u128));
+    ledout.set_morse("E", Repeat::Never).unwrap();
+    assert!(!expire(&mut ledout)?);
+    ledout.set_dsl("_#", Repeat::Forever).unwrap();
+    assert!(!expire(&mut ledout)?);
+    assert_eq!([1, 1, 1, 1, 1, 0], ledout.timer.started[..6]);
+    Ok(())
+}
+
+#[test]
+fn set_errors_keep_timer() {
+    let mut ledout = timed(MockedOutputPin::expected(0, 0b0_u128));
+    assert!(ledout.set_morse("?", Repeat::Never).is_err());
+    assert!(ledout.set_dsl("?", Repeat::Never).is_err());
+    let (_, timer) = ledout.release();
+    assert_eq!(1, timer.number_of_starts);
+}
+
+#[test]
+fn wrapped_output_is_accessible() {
+    let mut ledout = timed(MockedOutputPin::expected(0, 0b0_u128));
+    ledout
+        .output_mut()
+        .set_error_policy(ErrorPolicy::LatchFault);
+    assert!(!ledout.output().is_faulted());
+    assert_eq!(0, ledout.output().error_count());
+}
+
+#[test]
+fn pin_error_restarts_timer() -> Result<(), MockedOutputPinError> {
+    // the first attempt to drive the on state fails
+    let mut ledout = timed(MockedOutputPin::failing(3, 0b011_u128, 0b001));
+    ledout.set(0b01, 2, Repeat::Never);
+    assert!(expire(&mut ledout).is_err());
+    assert!(!ledout.update()?);
+    assert!(!expire(&mut ledout)?);
+    assert!(!expire(&mut ledout)?);
+    assert!(expire(&mut ledout)?);
+    assert_eq!([1, 1, 1, 1, 1, 0], ledout.timer.started[..6]);
+    assert_eq!(1, ledout.output().error_count());
+    Ok(())
+}
+
+#[test]
+fn retry_next_tick_swallows_error() -> Result<(), MockedOutputPinError> {
+    let mut ledout = timed(MockedOutputPin::failing(3, 0b011_u128, 0b001));
+    ledout
+        .output_mut()
+        .set_error_policy(ErrorPolicy::RetryNextTick);
+    ledout.set(0b01, 2, Repeat::Never);
+    assert!(!expire(&mut ledout)?);
+    assert!(!expire(&mut ledout)?);
+    assert!(!expire(&mut ledout)?);
+    assert!(expire(&mut ledout)?);
+    assert_eq!([1, 1, 1, 1, 1, 0], ledout.timer.started[..6]);
+    Ok(())
+}
+
+#[test]
+fn cleared_fault_resumes() -> Result<(), MockedOutputPinError> {
+    let mut ledout = timed(MockedOutputPin::failing(3, 0b011_u128, 0b001));
+    ledout
+        .output_mut()
+        .set_error_policy(ErrorPolicy::LatchFault);
+    ledout.set(0b01, 2, Repeat::Never);
+    assert!(expire(&mut ledout).is_err());
+    assert!(ledout.output().is_faulted());
+    assert!(expire(&mut ledout)?);
+    ledout.output_mut().clear_fault();
+    // the timer expired while faulted, the failed state is driven again
+    assert!(!ledout.update()?);
+    assert!(!expire(&mut ledout)?);
+    assert!(expire(&mut ledout)?);
+    assert_eq!([1, 1, 1, 1, 1, 0], ledout.timer.started[..6]);
+    Ok(())
+}
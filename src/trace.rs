@@ -0,0 +1,22 @@
+//! Optional instrumentation
+//!
+//! Events are emitted by `defmt` and/or `log`, depending on the enabled
+//! features. Without both features the events expand to nothing, the
+//! arguments are not even evaluated.
+//!
+//! The format strings have to be understood by both, so stick to `{}` and
+//! `{:?}` placeholders.
+
+/// Emits an event at the given level: `trace`, `debug`, `info`, `warn` or
+/// `error`
+macro_rules! event {
+    ($level:ident, $($arg:tt)+) => {{
+        #[cfg(feature = "defmt")]
+        defmt::$level!($($arg)+);
+        #[cfg(feature = "log")]
+        log::$level!($($arg)+);
+    }};
+}
+
+#[cfg(all(test, any(feature = "defmt", feature = "log")))]
+mod tests;
@@ -0,0 +1,132 @@
+/// Tests for the instrumentation
+use crate::prelude::*;
+use crate::tests::mocks::*;
+use embedded_hal::digital::v2::OutputPin;
+
+/// The test binary needs a defmt logger to link; its output is discarded
+#[cfg(feature = "defmt")]
+#[defmt::global_logger]
+struct DiscardingLogger;
+
+#[cfg(feature = "defmt")]
+unsafe impl defmt::Logger for DiscardingLogger {
+    fn acquire() {}
+    unsafe fn flush() {}
+    unsafe fn release() {}
+    unsafe fn write(_bytes: &[u8]) {}
+}
+
+/// Fails at every level driven
+struct FailingPin;
+
+impl OutputPin for FailingPin {
+    type Error = MockedOutputPinError;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Err(MockedOutputPinError)
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Err(MockedOutputPinError)
+    }
+}
+
+#[cfg(feature = "log")]
+mod log_events {
+    use super::*;
+    extern crate std;
+    use std::format;
+    use std::string::String;
+    use std::sync::Mutex;
+    use std::vec::Vec;
+
+    /// Collects the events of all tests, tests look for their own events
+    static EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct Capture;
+
+    impl log::Log for Capture {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            EVENTS
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", record.level(), record.args()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static CAPTURE: Capture = Capture;
+
+    fn capture() {
+        // fails if another test installed the logger already
+        let _ = log::set_logger(&CAPTURE);
+        log::set_max_level(log::LevelFilter::Trace);
+    }
+
+    fn emitted(event: &str) -> bool {
+        EVENTS
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|emitted| emitted == event)
+    }
+
+    #[test]
+    fn set() {
+        capture();
+        let pin_mock = MockedOutputPin::expected(0, 0b0_u128);
+        let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+        ledout.set(0b1, 11, Repeat::Times(4));
+        assert!(emitted("DEBUG set 11 output states, Times(4)"));
+    }
+
+    #[test]
+    fn set_morse_error() {
+        capture();
+        let pin_mock = MockedOutputPin::expected(0, 0b0_u128);
+        let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+        assert!(ledout.set_morse("~", Repeat::Never).is_err());
+        assert!(emitted("WARN set_morse failed: UnsupportedCode"));
+    }
+
+    #[test]
+    fn states_and_repetitions() -> Result<(), MockedOutputPinError> {
+        capture();
+        let pin_mock = MockedOutputPin::expected(6, 0b011_011_u128);
+        let mut ledout = OnOffSequenceOutput::new(pin_mock, 1);
+        ledout.set(0b011, 3, Repeat::Times(1));
+        for _ in 0..6 {
+            ledout.update()?;
+        }
+        assert!(emitted("TRACE state 0 of 3: true"));
+        assert!(emitted("TRACE state 2 of 3: false"));
+        assert!(emitted(
+            "DEBUG repetition boundary, Times(0), completed: false"
+        ));
+        assert!(emitted("DEBUG repetition boundary, Never, completed: true"));
+        Ok(())
+    }
+
+    #[test]
+    fn pin_error() {
+        capture();
+        let mut ledout = OnOffSequenceOutput::new(FailingPin, 1);
+        ledout.set(0b1, 1, Repeat::Never);
+        assert!(ledout.update().is_err());
+        assert!(emitted("WARN pin error driving true"));
+    }
+}
+
+#[cfg(feature = "defmt")]
+#[test]
+fn defmt_events_link() {
+    let mut ledout = OnOffSequenceOutput::new(FailingPin, 1);
+    ledout.set(0b1, 1, Repeat::Never);
+    assert!(ledout.set_morse("~", Repeat::Never).is_err());
+    assert!(ledout.update().is_err());
+}
@@ -0,0 +1,216 @@
+//! Readback verification of the driven level
+//!
+//! Safety indicators need to detect a stuck LED driver. A [`VerifiedOutput`]
+//! wraps an `OnOffSequenceOutput` and reads the level back after each
+//! transition, either from the output pin itself if it is a
+//! `StatefulOutputPin` ([`Readback`]), or from a separate sense `InputPin`
+//! ([`SenseInput`]). A level differing from the driven one is reported as
+//! [`VerifyError::Mismatch`].
+//!
+//! The readback happens a configurable number of updates after the
+//! transition, so the level can settle, e.g. behind an RC filter. The
+//! settling time has to be shorter than a state; a transition replacing a
+//! transition not read back yet is reported as [`VerifyError::Unverified`].
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use on_off_sequence_output::prelude::*;
+//! use on_off_sequence_output::verify::{SenseInput, VerifiedOutput, VerifyError};
+//!
+//! let led = OnOffSequenceOutput::new(led_pin, UPDATE_SCALE);
+//! // read back the sense input two updates after each transition
+//! let mut led = VerifiedOutput::new(led, SenseInput(sense_pin), 2);
+//! led.output_mut().set(0b0101, 4, Repeat::Forever);
+//! loop {
+//!    if let Err(VerifyError::Mismatch) = led.update() {
+//!        // the LED driver is stuck
+//!    }
+//!    wait(1.ms());
+//! }
+//! ```
+
+use crate::{OnOffSequenceOutput, OutputUpdate};
+use embedded_hal::digital::v2::{InputPin, OutputPin, StatefulOutputPin};
+
+/// Errors of a verified output
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VerifyError<E> {
+    /// Driving or reading back the level failed
+    Pin(E),
+    /// The level read back differs from the driven level
+    Mismatch,
+    /// A transition happened before the previous one was read back, i.e.
+    /// the settling time is not shorter than the state
+    Unverified,
+}
+
+/// Source of the level read back
+pub trait LevelSense<T: OutputPin> {
+    /// Reads the level of the output, true is high
+    ///
+    /// # Arguments
+    ///
+    /// * `pin` - The output pin driving the level
+    fn is_high(&mut self, pin: &T) -> Result<bool, T::Error>;
+}
+
+/// Reads the level back from the output pin itself
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Readback;
+
+impl<T: StatefulOutputPin> LevelSense<T> for Readback {
+    fn is_high(&mut self, pin: &T) -> Result<bool, T::Error> {
+        pin.is_set_high()
+    }
+}
+
+/// Reads the level back from a separate sense input
+pub struct SenseInput<I>(pub I);
+
+impl<T, I> LevelSense<T> for SenseInput<I>
+where
+    T: OutputPin,
+    I: InputPin<Error = T::Error>,
+{
+    fn is_high(&mut self, _pin: &T) -> Result<bool, T::Error> {
+        self.0.is_high()
+    }
+}
+
+/// A transition waiting for its readback
+#[derive(Clone, Copy)]
+struct Pending {
+    /// The driven level
+    high: bool,
+    /// Updates until the readback
+    ticks: u16,
+}
+
+/// Output verifying each transition by a readback
+pub struct VerifiedOutput<T: OutputPin, S: LevelSense<T>> {
+    output: OnOffSequenceOutput<T>,
+    sense: S,
+    /// Updates between a transition and its readback
+    settling_ticks: u16,
+    pending: Option<Pending>,
+    /// Number of mismatches, saturating
+    mismatch_count: u32,
+    /// Number of transitions not read back, saturating
+    unverified_count: u32,
+}
+
+impl<T: OutputPin, S: LevelSense<T>> VerifiedOutput<T, S> {
+    /// Initializes a new verified output
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The output to verify
+    /// * `sense` - Where the level is read back
+    /// * `settling_ticks` - Number of updates between a transition and its
+    ///   readback, zero reads back within the update of the transition
+    pub fn new(output: OnOffSequenceOutput<T>, sense: S, settling_ticks: u16) -> Self {
+        Self {
+            output,
+            sense,
+            settling_ticks,
+            pending: None,
+            mismatch_count: 0,
+            unverified_count: 0,
+        }
+    }
+
+    /// The verified output, e.g. to inspect it
+    pub fn output(&self) -> &OnOffSequenceOutput<T> {
+        &self.output
+    }
+
+    /// The verified output, e.g. to set a new sequence
+    pub fn output_mut(&mut self) -> &mut OnOffSequenceOutput<T> {
+        &mut self.output
+    }
+
+    /// Number of mismatches so far
+    pub fn mismatch_count(&self) -> u32 {
+        self.mismatch_count
+    }
+
+    /// Number of transitions replaced before their readback so far
+    pub fn unverified_count(&self) -> u32 {
+        self.unverified_count
+    }
+
+    /// Splits back into the output and the sense
+    pub fn release(self) -> (OnOffSequenceOutput<T>, S) {
+        (self.output, self.sense)
+    }
+
+    /// Reads the level back once the pending transition settled
+    fn verify(&mut self) -> Result<(), VerifyError<T::Error>> {
+        let pending = match self.pending {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
+        if pending.ticks > 0 {
+            self.pending = Some(Pending {
+                ticks: pending.ticks - 1,
+                ..pending
+            });
+            return Ok(());
+        }
+        self.pending = None;
+        let high = self
+            .sense
+            .is_high(&self.output.pin)
+            .map_err(VerifyError::Pin)?;
+        if high != pending.high {
+            event!(warn, "readback mismatch, driven {}", pending.high);
+            self.mismatch_count = self.mismatch_count.saturating_add(1);
+            return Err(VerifyError::Mismatch);
+        }
+        Ok(())
+    }
+}
+
+impl<T: OutputPin, S: LevelSense<T>> OutputUpdate for VerifiedOutput<T, S> {
+    type Error = VerifyError<T::Error>;
+
+    /// Updates the output and reads back the level of a settled transition
+    ///
+    /// # Returns
+    ///
+    /// * true - if the output is completed and its last transition verified
+    ///
+    /// # Notes
+    ///
+    /// * A transition before the readback of the previous one replaces it
+    ///   and is reported as `VerifyError::Unverified`, after the readback
+    ///   of the new transition if that is due at once
+    fn update(&mut self) -> Result<bool, Self::Error> {
+        let before = self.output.driven_level();
+        let completed = self.output.update().map_err(VerifyError::Pin)?;
+        let after = self.output.driven_level();
+        let mut replaced = false;
+        if let Some(high) = after {
+            if after != before {
+                replaced = self
+                    .pending
+                    .replace(Pending {
+                        high,
+                        ticks: self.settling_ticks,
+                    })
+                    .is_some();
+            }
+        }
+        self.verify()?;
+        if replaced {
+            event!(warn, "readback skipped, settling exceeds the state");
+            self.unverified_count = self.unverified_count.saturating_add(1);
+            return Err(VerifyError::Unverified);
+        }
+        Ok(completed && self.pending.is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests;
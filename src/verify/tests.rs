@@ -0,0 +1,115 @@
+/// Tests for the readback verification
+use super::*;
+use crate::tests::mocks::*;
+use crate::Repeat;
+
+/// A pin latching the driven level, unless stuck at a level
+struct LatchingPin {
+    high: bool,
+    stuck: Option<bool>,
+}
+
+impl LatchingPin {
+    fn new(stuck: Option<bool>) -> Self {
+        Self { high: false, stuck }
+    }
+}
+
+impl OutputPin for LatchingPin {
+    type Error = MockedOutputPinError;
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.high = self.stuck.unwrap_or(true);
+        Ok(())
+    }
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.high = self.stuck.unwrap_or(false);
+        Ok(())
+    }
+}
+
+impl StatefulOutputPin for LatchingPin {
+    fn is_set_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.high)
+    }
+
+    fn is_set_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.high)
+    }
+}
+
+/// A sense input at a fixed level
+struct FixedInput(bool);
+
+impl InputPin for FixedInput {
+    type Error = MockedOutputPinError;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        Ok(self.0)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        Ok(!self.0)
+    }
+}
+
+#[test]
+fn readback_matches() -> Result<(), VerifyError<MockedOutputPinError>> {
+    let ledout = OnOffSequenceOutput::new(LatchingPin::new(None), 1);
+    let mut ledout = VerifiedOutput::new(ledout, Readback, 0);
+    ledout.output_mut().set(0b0101, 4, Repeat::Times(1));
+    for _ in 1..8 {
+        assert!(!ledout.update()?);
+    }
+    assert!(ledout.update()?);
+    assert_eq!(0, ledout.mismatch_count());
+    Ok(())
+}
+
+#[test]
+fn readback_of_stuck_pin() {
+    let ledout = OnOffSequenceOutput::new(LatchingPin::new(Some(false)), 1);
+    let mut ledout = VerifiedOutput::new(ledout, Readback, 0);
+    ledout.output_mut().set(0b01, 2, Repeat::Never);
+    assert!(matches!(ledout.update(), Err(VerifyError::Mismatch)));
+    assert!(matches!(ledout.update(), Ok(true)));
+    assert_eq!(1, ledout.mismatch_count());
+}
+
+#[test]
+fn sense_input_after_settling() {
+    let pin_mock = MockedOutputPin::expected(4, 0b0011_u128);
+    let ledout = OnOffSequenceOutput::new(pin_mock, 1);
+    let mut ledout = VerifiedOutput::new(ledout, SenseInput(FixedInput(false)), 1);
+    ledout.output_mut().set(0b0011, 4, Repeat::Never);
+    assert!(matches!(ledout.update(), Ok(false)));
+    assert!(matches!(ledout.update(), Err(VerifyError::Mismatch)));
+    assert!(matches!(ledout.update(), Ok(false)));
+    assert!(matches!(ledout.update(), Ok(true)));
+    assert_eq!(1, ledout.mismatch_count());
+}
+
+#[test]
+fn completed_after_readback() {
+    let pin_mock = MockedOutputPin::expected(1, 0b0_u128);
+    let ledout = OnOffSequenceOutput::new(pin_mock, 1);
+    let mut ledout = VerifiedOutput::new(ledout, SenseInput(FixedInput(false)), 2);
+    ledout.output_mut().set(0b0, 1, Repeat::Never);
+    assert!(matches!(ledout.update(), Ok(false)));
+    assert!(matches!(ledout.update(), Ok(false)));
+    assert!(matches!(ledout.update(), Ok(true)));
+}
+
+#[test]
+fn settling_longer_than_a_state() {
+    let ledout = OnOffSequenceOutput::new(LatchingPin::new(Some(false)), 1);
+    let mut ledout = VerifiedOutput::new(ledout, Readback, 1);
+    ledout.output_mut().set(0b01, 2, Repeat::Forever);
+    assert!(matches!(ledout.update(), Ok(false)));
+    for _ in 0..1000 {
+        assert!(matches!(ledout.update(), Err(VerifyError::Unverified)));
+    }
+    assert_eq!(0, ledout.mismatch_count());
+    assert_eq!(1000, ledout.unverified_count());
+}